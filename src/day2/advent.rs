@@ -88,7 +88,7 @@ pub fn interpret_play_line(round: &str) -> (Play, Play)
     }
 }
 
-fn score_play(opponent: &Play, mine: &Play) -> u64
+pub fn score_play(opponent: &Play, mine: &Play) -> u64
 {
     match opponent
     {
@@ -122,7 +122,7 @@ fn score_play(opponent: &Play, mine: &Play) -> u64
     }
 }
 
-fn translate_round_str(round: &str) -> (Play, Play)
+pub fn translate_round_str(round: &str) -> (Play, Play)
 {
     let opponent: Play;
     let mine: Play;
@@ -153,7 +153,7 @@ fn translate_round_str(round: &str) -> (Play, Play)
     return (opponent, mine);
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Play
 {
     Rock = 1,
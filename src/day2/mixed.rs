@@ -0,0 +1,173 @@
+use super::advent::{Play, evaluate_tournament, evaluate_tournament_the_second, translate_round_str};
+use super::tournament::round_score;
+
+const PLAYS: [Play; 3] = [Play::Rock, Play::Paper, Play::Scissor];
+
+#[derive(Debug, PartialEq)]
+pub struct OpponentDistribution
+{
+    // Indexed rock, paper, scissor - i.e. A, B, C.
+    pub counts: [u64; 3],
+    pub total: u64,
+}
+
+impl OpponentDistribution
+{
+    pub fn probability(&self, play: &Play) -> f64
+    {
+        if self.total == 0
+        {
+            return 0.0;
+        }
+
+        self.counts[*play as usize - 1] as f64 / self.total as f64
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MixedStrategy
+{
+    // Probability of playing rock, paper and scissor respectively.
+    pub weights: [f64; 3],
+    // Every pure play that reaches the optimum; any mix of these scores the same.
+    pub optimal_plays: Vec<Play>,
+    // Expected score summed over every round in the guide.  Kept as an integer so that ties are exact.
+    pub expected_total: u64,
+    pub expected_per_round: f64,
+}
+
+pub struct SolverReport
+{
+    pub distribution: OpponentDistribution,
+    pub strategy: MixedStrategy,
+    pub guide_score: u64,
+    pub guide_score_the_second: u64,
+}
+
+pub fn opponent_distribution(rounds: &[&str]) -> OpponentDistribution
+{
+    let mut counts = [0u64; 3];
+
+    for round in rounds
+    {
+        if round.is_empty()
+        {
+            continue;
+        }
+
+        let (opponent, _) = translate_round_str(round);
+        counts[opponent as usize - 1] += 1;
+    }
+
+    OpponentDistribution { counts, total: counts.iter().sum() }
+}
+
+pub fn solve_mixed_strategy(distribution: &OpponentDistribution) -> MixedStrategy
+{
+    // The expected score is linear in our play weights, and the feasible region is the probability simplex.  A
+    // linear objective over a simplex always peaks on a vertex, so the LP is solved exactly by scoring the three
+    // pure plays against the observed counts and keeping the best.  Integer totals keep the comparison exact.
+    let mut totals = [0u64; 3];
+    for (mine_index, mine) in PLAYS.iter().enumerate()
+    {
+        for (theirs_index, theirs) in PLAYS.iter().enumerate()
+        {
+            totals[mine_index] += distribution.counts[theirs_index] * round_score(theirs, mine);
+        }
+    }
+
+    let best = *totals.iter().max().unwrap();
+    let optimal_plays: Vec<Play> = PLAYS.iter().zip(totals.iter()).filter(|(_, total)| **total == best).map(|(play, _)| *play).collect();
+
+    let mut weights = [0.0; 3];
+    weights[optimal_plays[0] as usize - 1] = 1.0;
+
+    let expected_per_round = if distribution.total == 0 { 0.0 } else { best as f64 / distribution.total as f64 };
+
+    MixedStrategy { weights, optimal_plays, expected_total: best, expected_per_round }
+}
+
+pub fn solve_guide(rounds: &[&str]) -> SolverReport
+{
+    let non_empty: Vec<&str> = rounds.iter().filter(|round| !round.is_empty()).copied().collect();
+
+    let distribution = opponent_distribution(&non_empty);
+    let strategy = solve_mixed_strategy(&distribution);
+
+    SolverReport
+    {
+        distribution,
+        strategy,
+        guide_score: evaluate_tournament(&non_empty),
+        guide_score_the_second: evaluate_tournament_the_second(&non_empty),
+    }
+}
+
+pub fn print_solution(report: &SolverReport)
+{
+    let distribution = &report.distribution;
+    println!("Opponent distribution over {} rounds: A {:.4}, B {:.4}, C {:.4}", distribution.total,
+        distribution.probability(&Play::Rock), distribution.probability(&Play::Paper), distribution.probability(&Play::Scissor));
+
+    let strategy = &report.strategy;
+    println!("Optimal strategy: rock {:.2}, paper {:.2}, scissor {:.2} (optimal pure plays: {:?})",
+        strategy.weights[0], strategy.weights[1], strategy.weights[2], strategy.optimal_plays);
+    println!("Expected score: {:.4} per round, {} over the whole guide", strategy.expected_per_round, strategy.expected_total);
+    println!("evaluate_tournament scored: {}", report.guide_score);
+    println!("evaluate_tournament_the_second scored: {}", report.guide_score_the_second);
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::day2::advent::Play;
+
+    use super::{opponent_distribution, solve_guide, solve_mixed_strategy, OpponentDistribution};
+
+    #[test]
+    pub fn opponent_distribution_counts_only_the_abc_column_and_skips_blank_lines()
+    {
+        let guide = vec!["A Y", "B X", "C Z", "A Z", ""];
+
+        let distribution = opponent_distribution(&guide);
+
+        assert_eq!(distribution.counts, [2, 1, 1]);
+        assert_eq!(distribution.total, 4);
+    }
+
+    #[test]
+    pub fn against_an_opponent_who_always_plays_rock_the_solver_always_plays_paper()
+    {
+        let distribution = OpponentDistribution { counts: [10, 0, 0], total: 10 };
+
+        let strategy = solve_mixed_strategy(&distribution);
+
+        assert_eq!(strategy.optimal_plays, vec![Play::Paper]);
+        assert_eq!(strategy.weights, [0.0, 1.0, 0.0]);
+        assert_eq!(strategy.expected_total, 80);
+        assert_eq!(strategy.expected_per_round, 8.0);
+    }
+
+    #[test]
+    pub fn against_a_uniform_opponent_scissor_wins_on_shape_value_alone()
+    {
+        let distribution = OpponentDistribution { counts: [1, 1, 1], total: 3 };
+
+        let strategy = solve_mixed_strategy(&distribution);
+
+        assert_eq!(strategy.optimal_plays, vec![Play::Scissor]);
+        assert_eq!(strategy.expected_total, 18);
+    }
+
+    #[test]
+    pub fn solve_guide_reports_the_guide_scores_alongside_the_optimum()
+    {
+        let guide = vec!["A Y", "B X", "C Z"];
+
+        let report = solve_guide(&guide);
+
+        assert_eq!(report.guide_score, 15);
+        assert_eq!(report.guide_score_the_second, 12);
+        assert_eq!(report.strategy.expected_total, 18);
+    }
+}
@@ -1 +1,3 @@
-pub mod advent;
\ No newline at end of file
+pub mod advent;
+pub mod tournament;
+pub mod mixed;
\ No newline at end of file
@@ -0,0 +1,418 @@
+use log::debug;
+
+use super::advent::{Play, score_play, translate_round_str};
+
+pub trait Strategy
+{
+    fn name(&self) -> String;
+
+    fn next_play(&mut self) -> Play;
+
+    fn observe(&mut self, opponent: &Play);
+
+    fn reset(&mut self);
+}
+
+pub fn round_score(opponent: &Play, mine: &Play) -> u64
+{
+    score_play(opponent, mine) + *mine as u64
+}
+
+pub fn beats(play: &Play) -> Play
+{
+    match play
+    {
+        Play::Rock => Play::Paper,
+        Play::Paper => Play::Scissor,
+        Play::Scissor => Play::Rock,
+    }
+}
+
+fn play_index(play: &Play) -> usize
+{
+    *play as usize - 1
+}
+
+const PLAYS: [Play; 3] = [Play::Rock, Play::Paper, Play::Scissor];
+
+#[derive(Default)]
+pub struct FrequencyCounter
+{
+    counts: [u64; 3],
+}
+
+impl FrequencyCounter
+{
+    pub fn new() -> FrequencyCounter
+    {
+        FrequencyCounter { counts: [0; 3] }
+    }
+}
+
+impl Strategy for FrequencyCounter
+{
+    fn name(&self) -> String
+    {
+        String::from("frequency-counter")
+    }
+
+    fn next_play(&mut self) -> Play
+    {
+        let mut favourite = 0;
+        for i in 1..3
+        {
+            if self.counts[i] > self.counts[favourite]
+            {
+                favourite = i;
+            }
+        }
+
+        beats(&PLAYS[favourite])
+    }
+
+    fn observe(&mut self, opponent: &Play)
+    {
+        self.counts[play_index(opponent)] += 1;
+    }
+
+    fn reset(&mut self)
+    {
+        self.counts = [0; 3];
+    }
+}
+
+pub struct RandomSeeded
+{
+    seed: u64,
+    state: u64,
+}
+
+impl RandomSeeded
+{
+    pub fn new(seed: u64) -> RandomSeeded
+    {
+        // xorshift gets stuck on zero forever, so nudge it off.
+        let seed = if seed == 0 { 0x9E3779B97F4A7C15 } else { seed };
+        RandomSeeded { seed, state: seed }
+    }
+
+    fn next_value(&mut self) -> u64
+    {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+
+        self.state
+    }
+}
+
+impl Strategy for RandomSeeded
+{
+    fn name(&self) -> String
+    {
+        format!("random-{}", self.seed)
+    }
+
+    fn next_play(&mut self) -> Play
+    {
+        let roll = self.next_value() % 3;
+        PLAYS[roll as usize]
+    }
+
+    fn observe(&mut self, _opponent: &Play) {}
+
+    fn reset(&mut self)
+    {
+        self.state = self.seed;
+    }
+}
+
+#[derive(Default)]
+pub struct Copycat
+{
+    last_seen: Option<Play>,
+}
+
+impl Copycat
+{
+    pub fn new() -> Copycat
+    {
+        Copycat { last_seen: None }
+    }
+}
+
+impl Strategy for Copycat
+{
+    fn name(&self) -> String
+    {
+        String::from("copycat")
+    }
+
+    fn next_play(&mut self) -> Play
+    {
+        self.last_seen.unwrap_or(Play::Rock)
+    }
+
+    fn observe(&mut self, opponent: &Play)
+    {
+        self.last_seen = Some(*opponent);
+    }
+
+    fn reset(&mut self)
+    {
+        self.last_seen = None;
+    }
+}
+
+pub struct GuideReplay
+{
+    name: String,
+    plays: Vec<Play>,
+    position: usize,
+}
+
+impl GuideReplay
+{
+    // Replays the XYZ column of a strategy guide, read the part 1 way (X = rock and so on).
+    pub fn mine(rounds: &[&str]) -> GuideReplay
+    {
+        let plays = rounds.iter().filter(|round| !round.is_empty()).map(|round| translate_round_str(round).1).collect();
+        GuideReplay { name: String::from("guide-mine"), plays, position: 0 }
+    }
+
+    // Replays the ABC column of a strategy guide, i.e. whatever the elves said the opponent would do.
+    pub fn theirs(rounds: &[&str]) -> GuideReplay
+    {
+        let plays = rounds.iter().filter(|round| !round.is_empty()).map(|round| translate_round_str(round).0).collect();
+        GuideReplay { name: String::from("guide-theirs"), plays, position: 0 }
+    }
+}
+
+impl Strategy for GuideReplay
+{
+    fn name(&self) -> String
+    {
+        self.name.clone()
+    }
+
+    fn next_play(&mut self) -> Play
+    {
+        if self.plays.is_empty()
+        {
+            return Play::Rock;
+        }
+
+        // Loop back to the start of the guide when a match outlasts it.
+        let play = self.plays[self.position % self.plays.len()];
+        self.position += 1;
+
+        play
+    }
+
+    fn observe(&mut self, _opponent: &Play) {}
+
+    fn reset(&mut self)
+    {
+        self.position = 0;
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MatchResult
+{
+    pub left: String,
+    pub right: String,
+    pub left_score: u64,
+    pub right_score: u64,
+    pub left_wins: u64,
+    pub right_wins: u64,
+    pub draws: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Standing
+{
+    pub name: String,
+    pub score: u64,
+    pub wins: u64,
+    pub draws: u64,
+    pub losses: u64,
+}
+
+pub struct TournamentResult
+{
+    pub matches: Vec<MatchResult>,
+    pub leaderboard: Vec<Standing>,
+}
+
+pub fn play_match(left: &mut dyn Strategy, right: &mut dyn Strategy, rounds: usize) -> MatchResult
+{
+    left.reset();
+    right.reset();
+
+    let mut result = MatchResult { left: left.name(), right: right.name(), left_score: 0, right_score: 0, left_wins: 0, right_wins: 0, draws: 0 };
+
+    for _ in 0..rounds
+    {
+        let left_play = left.next_play();
+        let right_play = right.next_play();
+
+        result.left_score += round_score(&right_play, &left_play);
+        result.right_score += round_score(&left_play, &right_play);
+
+        match score_play(&right_play, &left_play)
+        {
+            6 => result.left_wins += 1,
+            0 => result.right_wins += 1,
+            _ => result.draws += 1,
+        }
+
+        left.observe(&right_play);
+        right.observe(&left_play);
+    }
+
+    debug!("{} vs {}: {} to {}", result.left, result.right, result.left_score, result.right_score);
+
+    result
+}
+
+pub fn round_robin(bots: &mut [Box<dyn Strategy>], rounds: usize) -> TournamentResult
+{
+    let mut matches = Vec::new();
+    let mut leaderboard: Vec<Standing> = bots.iter().map(|bot| Standing { name: bot.name(), score: 0, wins: 0, draws: 0, losses: 0 }).collect();
+
+    for i in 0..bots.len()
+    {
+        for j in (i + 1)..bots.len()
+        {
+            let (head, tail) = bots.split_at_mut(j);
+            let result = play_match(head[i].as_mut(), tail[0].as_mut(), rounds);
+
+            leaderboard[i].score += result.left_score;
+            leaderboard[j].score += result.right_score;
+
+            if result.left_wins > result.right_wins
+            {
+                leaderboard[i].wins += 1;
+                leaderboard[j].losses += 1;
+            }
+            else if result.right_wins > result.left_wins
+            {
+                leaderboard[j].wins += 1;
+                leaderboard[i].losses += 1;
+            }
+            else
+            {
+                leaderboard[i].draws += 1;
+                leaderboard[j].draws += 1;
+            }
+
+            matches.push(result);
+        }
+    }
+
+    leaderboard.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.name.cmp(&b.name)));
+
+    TournamentResult { matches, leaderboard }
+}
+
+pub fn print_tournament(result: &TournamentResult)
+{
+    println!("Match breakdown:");
+    for game in &result.matches
+    {
+        println!("  {} ({}) vs {} ({}): {}-{}-{} (W-L-D)", game.left, game.left_score, game.right, game.right_score, game.left_wins, game.right_wins, game.draws);
+    }
+
+    println!("Leaderboard:");
+    for (place, standing) in result.leaderboard.iter().enumerate()
+    {
+        println!("  {}. {} - {} points, {} wins, {} draws, {} losses", place + 1, standing.name, standing.score, standing.wins, standing.draws, standing.losses);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::day2::advent::Play;
+
+    use super::{Copycat, FrequencyCounter, GuideReplay, RandomSeeded, Strategy, play_match, round_robin};
+
+    #[test]
+    pub fn frequency_counter_plays_whatever_beats_the_opponents_most_common_play()
+    {
+        let mut bot = FrequencyCounter::new();
+
+        bot.observe(&Play::Scissor);
+        bot.observe(&Play::Scissor);
+        bot.observe(&Play::Paper);
+
+        assert_eq!(bot.next_play(), Play::Rock);
+    }
+
+    #[test]
+    pub fn copycat_plays_the_opponents_previous_move()
+    {
+        let mut bot = Copycat::new();
+
+        assert_eq!(bot.next_play(), Play::Rock);
+        bot.observe(&Play::Paper);
+        assert_eq!(bot.next_play(), Play::Paper);
+    }
+
+    #[test]
+    pub fn random_seeded_bots_with_the_same_seed_repeat_after_reset()
+    {
+        let mut bot = RandomSeeded::new(42);
+        let first: Vec<Play> = (0..20).map(|_| bot.next_play()).collect();
+
+        bot.reset();
+        let second: Vec<Play> = (0..20).map(|_| bot.next_play()).collect();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    pub fn guide_replay_cycles_through_the_guide_and_skips_blank_lines()
+    {
+        let guide = vec!["A Y", "B X", "", "C Z"];
+        let mut mine = GuideReplay::mine(&guide);
+        let mut theirs = GuideReplay::theirs(&guide);
+
+        let my_plays: Vec<Play> = (0..4).map(|_| mine.next_play()).collect();
+        let their_plays: Vec<Play> = (0..4).map(|_| theirs.next_play()).collect();
+
+        assert_eq!(my_plays, vec![Play::Paper, Play::Rock, Play::Scissor, Play::Paper]);
+        assert_eq!(their_plays, vec![Play::Rock, Play::Paper, Play::Scissor, Play::Rock]);
+    }
+
+    #[test]
+    pub fn replaying_both_guide_columns_against_each_other_scores_the_same_as_evaluate_tournament()
+    {
+        let guide = vec!["A Y", "B X", "C Z"];
+        let mut mine = GuideReplay::mine(&guide);
+        let mut theirs = GuideReplay::theirs(&guide);
+
+        let result = play_match(&mut mine, &mut theirs, guide.len());
+
+        assert_eq!(result.left_score, 15);
+        assert_eq!(result.left_wins, 1);
+        assert_eq!(result.right_wins, 1);
+        assert_eq!(result.draws, 1);
+    }
+
+    #[test]
+    pub fn round_robin_plays_every_pair_once_and_ranks_by_score()
+    {
+        let guide = vec!["A Y", "A Y", "A Y"];
+        let mut bots: Vec<Box<dyn Strategy>> = vec![Box::new(GuideReplay::theirs(&guide)), Box::new(FrequencyCounter::new()), Box::new(Copycat::new())];
+
+        let result = round_robin(&mut bots, 9);
+
+        assert_eq!(result.matches.len(), 3);
+        assert_eq!(result.leaderboard.len(), 3);
+        assert_eq!(result.leaderboard[0].name, "frequency-counter");
+        assert!(result.leaderboard[0].score >= result.leaderboard[1].score);
+        assert!(result.leaderboard[1].score >= result.leaderboard[2].score);
+    }
+}
@@ -120,7 +120,7 @@ pub fn transform_priorities(overlaps: &Vec<char>) -> Vec<u64>
     return priorities;
 }
 
-fn char_to_num(src: &char) -> u8
+pub fn char_to_num(src: &char) -> u8
 {
     // sigh
     match src
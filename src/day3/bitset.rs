@@ -0,0 +1,236 @@
+use log::debug;
+
+use super::advent::char_to_num;
+
+// Each item type maps to one bit of a u64: bit 0 is 'a' (priority 1) through bit 51 for 'Z' (priority 52).
+pub fn item_bit(item: &char) -> u64
+{
+    1u64 << (char_to_num(item) - 1)
+}
+
+pub fn to_mask(items: &str) -> u64
+{
+    let mut mask = 0u64;
+
+    for item in items.chars()
+    {
+        mask |= item_bit(&item);
+    }
+
+    mask
+}
+
+pub fn mask_to_types(mask: u64) -> Vec<char>
+{
+    let mut types = Vec::new();
+
+    for bit in 0..52u8
+    {
+        if mask & (1u64 << bit) != 0
+        {
+            if bit < 26
+            {
+                types.push((b'a' + bit) as char);
+            }
+            else
+            {
+                types.push((b'A' + bit - 26) as char);
+            }
+        }
+    }
+
+    types
+}
+
+pub fn mask_priority(mask: u64) -> u64
+{
+    let mut priority = 0u64;
+    let mut remaining = mask;
+
+    while remaining != 0
+    {
+        priority += remaining.trailing_zeros() as u64 + 1;
+        remaining &= remaining - 1;
+    }
+
+    priority
+}
+
+pub fn split_compartments(sack: &str, compartments: usize) -> Vec<&str>
+{
+    if compartments == 0 || !sack.len().is_multiple_of(compartments)
+    {
+        panic!("A sack of {} items cannot be split into {} equal compartments.", sack.len(), compartments);
+    }
+
+    let width = sack.len() / compartments;
+    let mut split = Vec::with_capacity(compartments);
+
+    for i in 0..compartments
+    {
+        split.push(&sack[i * width..(i + 1) * width]);
+    }
+
+    split
+}
+
+// Types found in every compartment of a sack.
+pub fn shared_in_sack(sack: &str, compartments: usize) -> u64
+{
+    let mut shared = u64::MAX;
+
+    for compartment in split_compartments(sack, compartments)
+    {
+        shared &= to_mask(compartment);
+    }
+
+    shared
+}
+
+// Types found in every sack of a group, regardless of compartment.
+pub fn shared_in_group(group: &[&str]) -> u64
+{
+    if group.is_empty()
+    {
+        return 0;
+    }
+
+    let mut shared = u64::MAX;
+
+    for sack in group
+    {
+        shared &= to_mask(sack);
+    }
+
+    shared
+}
+
+pub fn analyze_rucksacks_bitset(sacks: &[&str], compartments: usize) -> u64
+{
+    let mut running_total: u64 = 0;
+
+    for sack in sacks
+    {
+        if sack.is_empty()
+        {
+            continue;
+        }
+
+        running_total += mask_priority(shared_in_sack(sack, compartments));
+    }
+
+    running_total
+}
+
+pub fn check_group_size(group_size: usize)
+{
+    if group_size == 0
+    {
+        panic!("A group has to have at least one sack in it.");
+    }
+}
+
+// Every badge candidate for each group of group_size sacks.  A trailing group with fewer than group_size sacks is
+// still analyzed with whatever members it has.
+pub fn find_group_badges(sacks: &[&str], group_size: usize) -> Vec<Vec<char>>
+{
+    check_group_size(group_size);
+
+    let non_empty: Vec<&str> = sacks.iter().filter(|sack| !sack.is_empty()).copied().collect();
+    let mut badges = Vec::new();
+
+    for group in non_empty.chunks(group_size)
+    {
+        if group.len() != group_size
+        {
+            debug!("The final group only has {} of {} sacks.", group.len(), group_size);
+        }
+
+        badges.push(mask_to_types(shared_in_group(group)));
+    }
+
+    badges
+}
+
+pub fn analyze_badges_bitset(sacks: &[&str], group_size: usize) -> u64
+{
+    check_group_size(group_size);
+    let non_empty: Vec<&str> = sacks.iter().filter(|sack| !sack.is_empty()).copied().collect();
+
+    non_empty.chunks(group_size).map(|group| mask_priority(shared_in_group(group))).sum()
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day3::advent::{analyze_badges, analyze_rucksacks};
+
+    use super::{analyze_badges_bitset, analyze_rucksacks_bitset, find_group_badges, mask_priority, mask_to_types, shared_in_sack, split_compartments, to_mask};
+
+    const SAMPLE: [&str; 6] = [
+        "vJrwpWtwJgWrhcsFMMfFFhFp",
+        "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+        "PmmdzqPrVvPwwTWBwg",
+        "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+        "ttgJtRGJQctTZtZT",
+        "CrZsJsPPZsGzwwsLwLmpwMDw",
+    ];
+
+    #[test]
+    pub fn to_mask_and_mask_to_types_round_trip_the_distinct_types()
+    {
+        let mask = to_mask("aaZzb");
+
+        assert_eq!(mask.count_ones(), 4);
+        assert_eq!(mask_to_types(mask), vec!['a', 'b', 'z', 'Z']);
+        assert_eq!(mask_priority(mask), 1 + 2 + 26 + 52);
+    }
+
+    #[test]
+    pub fn split_compartments_divides_a_sack_into_equal_parts()
+    {
+        assert_eq!(split_compartments("abcdef", 3), vec!["ab", "cd", "ef"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one sack")]
+    pub fn a_group_size_of_zero_is_rejected()
+    {
+        analyze_badges_bitset(&SAMPLE, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    pub fn split_compartments_panics_when_the_sack_does_not_divide_evenly()
+    {
+        split_compartments("abcde", 2);
+    }
+
+    #[test]
+    pub fn shared_in_sack_reports_every_type_common_to_all_compartments()
+    {
+        assert_eq!(mask_to_types(shared_in_sack("abXcbaXc", 2)), vec!['a', 'b', 'c', 'X']);
+        assert_eq!(mask_to_types(shared_in_sack("abcabdaxe", 3)), vec!['a']);
+    }
+
+    #[test]
+    pub fn bitset_analysis_matches_the_hashset_analysis_on_the_sample()
+    {
+        let sacks = SAMPLE.to_vec();
+
+        assert_eq!(analyze_rucksacks_bitset(&sacks, 2), analyze_rucksacks(&sacks));
+        assert_eq!(analyze_badges_bitset(&sacks, 3), analyze_badges(&sacks));
+        assert_eq!(analyze_badges_bitset(&sacks, 3), 70);
+    }
+
+    #[test]
+    pub fn find_group_badges_reports_all_candidates_and_empty_groups_without_panicking()
+    {
+        let sacks = vec!["abc", "bca", "xyz", "xyq"];
+
+        let badges = find_group_badges(&sacks, 2);
+
+        assert_eq!(badges, vec![vec!['a', 'b', 'c'], vec!['x', 'y']]);
+        assert_eq!(find_group_badges(&["ab", "cd"], 2), vec![Vec::<char>::new()]);
+    }
+}
@@ -1 +1,4 @@
-pub mod advent;
\ No newline at end of file
+pub mod advent;
+pub mod bitset;
+pub mod repack;
+pub mod report;
\ No newline at end of file
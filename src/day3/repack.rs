@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+use log::debug;
+
+use super::advent::{analyze_compartments, transform_priorities};
+
+#[derive(Debug, PartialEq)]
+pub struct RepackPlan
+{
+    pub sack_index: usize,
+    // Each swap trades the first item (leaving the left compartment) for the second (leaving the right compartment).
+    pub swaps: Vec<(char, char)>,
+    pub repacked: String,
+    pub priority_saved: u64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RepackReport
+{
+    pub plans: Vec<RepackPlan>,
+    pub impossible: Vec<usize>,
+    pub priority_saved: u64,
+}
+
+pub fn plan_repacking(sacks: &[&str]) -> RepackReport
+{
+    let mut plans = Vec::new();
+    let mut impossible = Vec::new();
+    let mut priority_saved: u64 = 0;
+
+    for (sack_index, sack) in sacks.iter().enumerate()
+    {
+        if sack.is_empty()
+        {
+            continue;
+        }
+
+        if let Some(plan) = plan_sack(sack_index, sack)
+        {
+            priority_saved += plan.priority_saved;
+            plans.push(plan);
+        }
+        else
+        {
+            debug!("Sack {} cannot be repacked without duplicates.", sack_index);
+            impossible.push(sack_index);
+        }
+    }
+
+    RepackReport { plans, impossible, priority_saved }
+}
+
+// Moving items one way only would unbalance the compartments, so every move is really a swap.  Picking which
+// compartment each item type ends up in is a subset sum: the types sent left must add up to exactly half the sack.
+// Among those subsets we want the one that leaves the fewest items out of place, which a DP over the sum finds.
+pub fn plan_sack(sack_index: usize, sack: &str) -> Option<RepackPlan>
+{
+    if !sack.len().is_multiple_of(2)
+    {
+        return None;
+    }
+
+    let half = sack.len() / 2;
+    let (left, right) = sack.split_at(half);
+
+    // type -> (count in left, count in right)
+    let mut counts = BTreeMap::<char, (usize, usize)>::new();
+    for item in left.chars()
+    {
+        counts.entry(item).or_insert((0, 0)).0 += 1;
+    }
+    for item in right.chars()
+    {
+        counts.entry(item).or_insert((0, 0)).1 += 1;
+    }
+
+    let types: Vec<(char, usize, usize)> = counts.into_iter().map(|(item, (l, r))| (item, l, r)).collect();
+
+    // best[i][s]: fewest displaced items using the first i types with s items placed on the left.
+    let mut best = vec![vec![None; half + 1]; types.len() + 1];
+    let mut went_left = vec![vec![false; half + 1]; types.len() + 1];
+    best[0][0] = Some(0usize);
+
+    for (i, (_, l, r)) in types.iter().enumerate()
+    {
+        let total = l + r;
+        for s in 0..=half
+        {
+            if let Some(cost) = best[i][s]
+            {
+                // Everything of this type stays right, so the l copies on the left have to move.
+                if best[i + 1][s].is_none_or(|current| cost + l < current)
+                {
+                    best[i + 1][s] = Some(cost + l);
+                    went_left[i + 1][s] = false;
+                }
+
+                // Everything of this type goes left, so the r copies on the right have to move.
+                if s + total <= half && best[i + 1][s + total].is_none_or(|current| cost + r < current)
+                {
+                    best[i + 1][s + total] = Some(cost + r);
+                    went_left[i + 1][s + total] = true;
+                }
+            }
+        }
+    }
+
+    best[types.len()][half]?;
+
+    let mut to_left = Vec::new();
+    let mut to_right = Vec::new();
+    let mut new_left = String::new();
+    let mut new_right = String::new();
+    let mut s = half;
+
+    for i in (0..types.len()).rev()
+    {
+        let (item, l, r) = types[i];
+        if went_left[i + 1][s]
+        {
+            to_left.extend(std::iter::repeat_n(item, r));
+            new_left.extend(std::iter::repeat_n(item, l + r));
+            s -= l + r;
+        }
+        else
+        {
+            to_right.extend(std::iter::repeat_n(item, l));
+            new_right.extend(std::iter::repeat_n(item, l + r));
+        }
+    }
+
+    let swaps: Vec<(char, char)> = to_right.into_iter().zip(to_left).collect();
+    let priority_saved = transform_priorities(&analyze_compartments(left, right)).iter().sum();
+
+    new_left.push_str(&new_right);
+
+    Some(RepackPlan { sack_index, swaps, repacked: new_left, priority_saved })
+}
+
+pub fn print_repacking(report: &RepackReport)
+{
+    for plan in &report.plans
+    {
+        if plan.swaps.is_empty()
+        {
+            continue;
+        }
+
+        let swaps: Vec<String> = plan.swaps.iter().map(|(out, back)| format!("{}<->{}", out, back)).collect();
+        println!("Sack {}: {} swap(s) [{}], saves priority {}", plan.sack_index, plan.swaps.len(), swaps.join(" "), plan.priority_saved);
+    }
+
+    println!("Sacks that cannot be repacked: {:?}", report.impossible);
+    println!("Total priority saved: {}", report.priority_saved);
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day3::advent::{analyze_compartments, analyze_rucksacks};
+
+    use super::{plan_repacking, plan_sack};
+
+    #[test]
+    pub fn a_sack_with_no_duplicates_needs_no_swaps()
+    {
+        let plan = plan_sack(0, "abcdef").unwrap();
+
+        assert!(plan.swaps.is_empty());
+        assert_eq!(plan.priority_saved, 0);
+    }
+
+    #[test]
+    pub fn a_single_shared_type_is_fixed_with_one_swap()
+    {
+        // a is in both halves; swapping the right a for the left c fixes it.
+        let plan = plan_sack(0, "abcade").unwrap();
+        let (left, right) = plan.repacked.split_at(3);
+
+        assert_eq!(plan.swaps.len(), 1);
+        assert_eq!(plan.priority_saved, 1);
+        assert!(analyze_compartments(left, right).is_empty());
+    }
+
+    #[test]
+    pub fn a_type_filling_more_than_half_the_sack_is_impossible()
+    {
+        assert!(plan_sack(0, "aaab").is_none());
+        assert!(plan_sack(0, "abc").is_none());
+    }
+
+    #[test]
+    pub fn repacking_the_sample_separates_every_feasible_sack_and_saves_at_most_the_analyzed_priority()
+    {
+        let sacks = vec![
+            "vJrwpWtwJgWrhcsFMMfFFhFp",
+            "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+            "PmmdzqPrVvPwwTWBwg",
+            "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+            "ttgJtRGJQctTZtZT",
+            "CrZsJsPPZsGzwwsLwLmpwMDw",
+            "",
+        ];
+
+        let report = plan_repacking(&sacks);
+
+        let possible: u64 = report.plans.iter().map(|plan| plan.priority_saved).sum();
+        assert_eq!(report.priority_saved, possible);
+        assert_eq!(report.plans.len() + report.impossible.len(), 6);
+        for plan in &report.plans
+        {
+            let (left, right) = plan.repacked.split_at(plan.repacked.len() / 2);
+            assert!(analyze_compartments(left, right).is_empty());
+        }
+        assert!(report.priority_saved <= analyze_rucksacks(&sacks));
+    }
+}
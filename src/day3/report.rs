@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, HashSet};
+
+use super::advent::{analyze_compartments, transform_priorities};
+use super::bitset::check_group_size;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum OutputFormat
+{
+    Text,
+    Json,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ItemFrequency
+{
+    pub item: char,
+    // Number of sacks holding at least one of the item.
+    pub sacks: usize,
+    // Number of copies across every sack.
+    pub occurrences: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InventoryReport
+{
+    pub sack_count: usize,
+    pub item_frequency: Vec<ItemFrequency>,
+    pub co_occurrence: Vec<(char, char, usize)>,
+    // number of types shared between compartments -> number of sacks with that many
+    pub duplicate_distribution: BTreeMap<usize, usize>,
+    pub duplicate_priority: u64,
+    pub badge_candidates: Vec<Vec<char>>,
+}
+
+pub fn build_report(sacks: &[&str], group_size: usize, top_pairs: usize) -> InventoryReport
+{
+    check_group_size(group_size);
+    let non_empty: Vec<&str> = sacks.iter().filter(|sack| !sack.is_empty()).copied().collect();
+
+    let mut frequency = BTreeMap::<char, (usize, usize)>::new();
+    let mut duplicate_distribution = BTreeMap::<usize, usize>::new();
+    let mut duplicate_priority: u64 = 0;
+
+    for sack in &non_empty
+    {
+        let mut seen = HashSet::<char>::new();
+        for item in sack.chars()
+        {
+            let entry = frequency.entry(item).or_insert((0, 0));
+            entry.1 += 1;
+            if seen.insert(item)
+            {
+                entry.0 += 1;
+            }
+        }
+
+        let (left, right) = sack.split_at(sack.len() / 2);
+        let duplicates = analyze_compartments(left, right);
+        duplicate_priority += transform_priorities(&duplicates).iter().sum::<u64>();
+        *duplicate_distribution.entry(duplicates.len()).or_insert(0) += 1;
+    }
+
+    let mut item_frequency: Vec<ItemFrequency> = frequency.into_iter().map(|(item, (sacks, occurrences))| ItemFrequency { item, sacks, occurrences }).collect();
+    item_frequency.sort_by(|a, b| b.sacks.cmp(&a.sacks).then(b.occurrences.cmp(&a.occurrences)).then(a.item.cmp(&b.item)));
+
+    let mut pair_counts = BTreeMap::<(char, char), usize>::new();
+    let mut badge_candidates = Vec::new();
+
+    for group in non_empty.chunks(group_size)
+    {
+        let mut present: Vec<char> = group.iter().flat_map(|sack| sack.chars()).collect::<HashSet<char>>().into_iter().collect();
+        present.sort();
+
+        for i in 0..present.len()
+        {
+            for j in (i + 1)..present.len()
+            {
+                *pair_counts.entry((present[i], present[j])).or_insert(0) += 1;
+            }
+        }
+
+        badge_candidates.push(group_candidates(group));
+    }
+
+    let mut co_occurrence: Vec<(char, char, usize)> = pair_counts.into_iter().map(|((a, b), count)| (a, b, count)).collect();
+    co_occurrence.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)).then(a.1.cmp(&b.1)));
+    co_occurrence.truncate(top_pairs);
+
+    InventoryReport { sack_count: non_empty.len(), item_frequency, co_occurrence, duplicate_distribution, duplicate_priority, badge_candidates }
+}
+
+// Narrows the candidates down one sack at a time, the same way analyze_badges compares neighbouring sacks.
+fn group_candidates(group: &[&str]) -> Vec<char>
+{
+    if group.is_empty()
+    {
+        return Vec::new();
+    }
+
+    let mut candidates: String = group[0].to_string();
+    for sack in &group[1..]
+    {
+        candidates = analyze_compartments(&candidates, sack).into_iter().collect();
+    }
+
+    let mut unique: Vec<char> = candidates.chars().collect::<HashSet<char>>().into_iter().collect();
+    unique.sort();
+
+    unique
+}
+
+pub fn render_report(report: &InventoryReport, format: OutputFormat) -> String
+{
+    match format
+    {
+        OutputFormat::Text => render_text(report),
+        OutputFormat::Json => render_json(report),
+    }
+}
+
+fn render_text(report: &InventoryReport) -> String
+{
+    let mut out = String::new();
+
+    out.push_str(&format!("Sacks analyzed: {}\n", report.sack_count));
+
+    out.push_str("Item frequency (sacks / copies):\n");
+    for freq in &report.item_frequency
+    {
+        out.push_str(&format!("  {}: {} / {}\n", freq.item, freq.sacks, freq.occurrences));
+    }
+
+    out.push_str("Most common co-occurring pairs across groups:\n");
+    for (a, b, count) in &report.co_occurrence
+    {
+        out.push_str(&format!("  {}{}: {} groups\n", a, b, count));
+    }
+
+    out.push_str("Duplicate types per sack:\n");
+    for (duplicates, sacks) in &report.duplicate_distribution
+    {
+        out.push_str(&format!("  {} duplicate type(s): {} sacks\n", duplicates, sacks));
+    }
+    out.push_str(&format!("Total duplicate priority: {}\n", report.duplicate_priority));
+
+    out.push_str("Badge candidates per group:\n");
+    for (group, candidates) in report.badge_candidates.iter().enumerate()
+    {
+        let listed: String = candidates.iter().collect();
+        out.push_str(&format!("  group {}: {}\n", group, listed));
+    }
+
+    out
+}
+
+fn render_json(report: &InventoryReport) -> String
+{
+    // Item types are always a-zA-Z so nothing in here ever needs escaping.
+    let frequency: Vec<String> = report.item_frequency.iter()
+        .map(|freq| format!("{{\"item\":\"{}\",\"sacks\":{},\"occurrences\":{}}}", freq.item, freq.sacks, freq.occurrences))
+        .collect();
+    let pairs: Vec<String> = report.co_occurrence.iter()
+        .map(|(a, b, count)| format!("{{\"pair\":[\"{}\",\"{}\"],\"groups\":{}}}", a, b, count))
+        .collect();
+    let distribution: Vec<String> = report.duplicate_distribution.iter()
+        .map(|(duplicates, sacks)| format!("\"{}\":{}", duplicates, sacks))
+        .collect();
+    let badges: Vec<String> = report.badge_candidates.iter()
+        .map(|candidates| format!("[{}]", candidates.iter().map(|c| format!("\"{}\"", c)).collect::<Vec<String>>().join(",")))
+        .collect();
+
+    format!("{{\"sack_count\":{},\"item_frequency\":[{}],\"co_occurrence\":[{}],\"duplicate_distribution\":{{{}}},\"duplicate_priority\":{},\"badge_candidates\":[{}]}}",
+        report.sack_count, frequency.join(","), pairs.join(","), distribution.join(","), report.duplicate_priority, badges.join(","))
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day3::advent::analyze_rucksacks;
+
+    use super::{build_report, render_report, OutputFormat};
+
+    const SAMPLE: [&str; 6] = [
+        "vJrwpWtwJgWrhcsFMMfFFhFp",
+        "jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL",
+        "PmmdzqPrVvPwwTWBwg",
+        "wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn",
+        "ttgJtRGJQctTZtZT",
+        "CrZsJsPPZsGzwwsLwLmpwMDw",
+    ];
+
+    #[test]
+    pub fn the_report_agrees_with_the_existing_day_3_answers()
+    {
+        let sacks = SAMPLE.to_vec();
+
+        let report = build_report(&sacks, 3, 5);
+
+        assert_eq!(report.sack_count, 6);
+        assert_eq!(report.duplicate_priority, analyze_rucksacks(&sacks));
+        assert_eq!(report.badge_candidates, vec![vec!['r'], vec!['Z']]);
+        assert_eq!(report.duplicate_distribution.get(&1), Some(&6));
+        assert_eq!(report.co_occurrence.len(), 5);
+    }
+
+    #[test]
+    pub fn item_frequency_counts_sacks_and_copies_separately()
+    {
+        let report = build_report(&["aabb", "abcd"], 2, 1);
+
+        let a = report.item_frequency.iter().find(|freq| freq.item == 'a').unwrap();
+        assert_eq!(a.sacks, 2);
+        assert_eq!(a.occurrences, 3);
+        assert_eq!(report.item_frequency[0].item, 'a');
+        assert_eq!(report.co_occurrence, vec![('a', 'b', 1)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one sack")]
+    pub fn a_report_with_a_group_size_of_zero_is_rejected()
+    {
+        build_report(&SAMPLE, 0, 3);
+    }
+
+    #[test]
+    pub fn json_output_contains_every_section()
+    {
+        let report = build_report(&["abca", "dbed"], 2, 2);
+
+        let json = render_report(&report, OutputFormat::Json);
+
+        assert!(json.starts_with("{\"sack_count\":2,"));
+        assert!(json.contains("\"duplicate_distribution\":{\"1\":2}"));
+        assert!(json.contains("\"badge_candidates\":[[\"b\"]]"));
+        assert!(render_report(&report, OutputFormat::Text).contains("group 0: b"));
+    }
+}
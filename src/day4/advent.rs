@@ -1,3 +1,5 @@
+use super::interval::{IntervalSet, Range};
+
 
 pub fn count_contained_pairs(pairs: Vec<&str>) -> (u64, u64)
 {
@@ -12,12 +14,16 @@ pub fn count_contained_pairs(pairs: Vec<&str>) -> (u64, u64)
         }
 
         let (elf_1_range, elf_2_range) = construct_range_pair(pair_str);
-        if contains(&elf_1_range, &elf_2_range) || contains(&elf_2_range, &elf_1_range)
+        let elf_1 = IntervalSet::from_ranges([Range::from(&elf_1_range)]);
+        let elf_2 = IntervalSet::from_ranges([Range::from(&elf_2_range)]);
+
+        // One assignment holds the other exactly when removing it leaves nothing behind.
+        if elf_2.difference(&elf_1).is_empty() || elf_1.difference(&elf_2).is_empty()
         {
             fully_contained += 1;
         }
 
-        if overlaps(&elf_1_range, &elf_2_range)
+        if !elf_1.intersection(&elf_2).is_empty()
         {
             any_overlap += 1;
         }
@@ -82,10 +88,16 @@ impl SectionRange {
 #[cfg(test)]
 pub mod tests
 {
-    use crate::day4::advent::{SectionRange, contains, overlaps};
+    use crate::day4::advent::{SectionRange, contains, count_contained_pairs, overlaps};
 
     use super::construct_range_pair;
 
+    #[test]
+    pub fn count_contained_pairs_reads_a_reversed_assignment_as_the_same_sections()
+    {
+        assert_eq!(count_contained_pairs(vec!["5-3,4-4", "8-6,1-2"]), (1, 1));
+    }
+
     #[test]
     pub fn overlaps_returns_false_on_disjoint_ranges_where_range_1_ends_before_range_2()
     {
@@ -187,4 +199,12 @@ pub mod tests
         assert!(!contains(&intersecting_range_2, &intersecting_range_1));
         assert!(contains(&intersecting_range_1, &intersecting_range_2));
     }
+
+    #[test]
+    pub fn count_contained_pairs_finds_two_contained_and_four_overlapping_pairs_in_the_sample()
+    {
+        let pairs = vec!["2-4,6-8", "2-3,4-5", "5-7,7-9", "2-8,3-7", "6-6,4-6", "2-6,4-8", ""];
+
+        assert_eq!(count_contained_pairs(pairs), (2, 4));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,312 @@
+use std::collections::{BTreeMap, HashMap};
+
+use super::interval::{IntervalSet, Range};
+use super::population::Assignment;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CoverageRun
+{
+    pub range: Range<u16>,
+    pub owners: u32,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CoverageMap
+{
+    pub bounds: Range<u16>,
+    // Maximal runs of sections with the same number of owners, in order and covering all of bounds.
+    pub runs: Vec<CoverageRun>,
+}
+
+impl CoverageMap
+{
+    pub fn gaps(&self) -> Vec<Range<u16>>
+    {
+        self.runs.iter().filter(|run| run.owners == 0).map(|run| run.range).collect()
+    }
+
+    pub fn hot_spots(&self, min_owners: u32) -> Vec<CoverageRun>
+    {
+        self.runs.iter().filter(|run| run.owners >= min_owners).copied().collect()
+    }
+
+    // owners -> number of sections with exactly that many owners
+    pub fn histogram(&self) -> BTreeMap<u32, u64>
+    {
+        let mut histogram = BTreeMap::new();
+        for run in &self.runs
+        {
+            *histogram.entry(run.owners).or_insert(0) += (run.range.end - run.range.start) as u64 + 1;
+        }
+
+        histogram
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Reassignment
+{
+    pub assignment: usize,
+    pub from: Range<u16>,
+    // None when the elf can simply be released.
+    pub to: Option<Range<u16>>,
+}
+
+fn default_bounds(assignments: &[Assignment]) -> Option<Range<u16>>
+{
+    let start = assignments.iter().map(|a| a.range.start).min()?;
+    let end = assignments.iter().map(|a| a.range.end).max()?;
+
+    Some(Range::new(start, end))
+}
+
+pub fn coverage_map(assignments: &[Assignment], bounds: Option<Range<u16>>) -> Option<CoverageMap>
+{
+    let bounds = bounds.or_else(|| default_bounds(assignments))?;
+
+    // Difference array: +1 where a range starts, -1 just past where it ends.  A running sum gives the owner count.
+    let width = (bounds.end - bounds.start) as usize + 1;
+    let mut deltas = vec![0i64; width + 1];
+    for assignment in assignments
+    {
+        if let Some(clipped) = assignment.range.intersection(&bounds)
+        {
+            deltas[(clipped.start - bounds.start) as usize] += 1;
+            deltas[(clipped.end - bounds.start) as usize + 1] -= 1;
+        }
+    }
+
+    let mut runs: Vec<CoverageRun> = Vec::new();
+    let mut owners: i64 = 0;
+    for (offset, delta) in deltas.iter().take(width).enumerate()
+    {
+        owners += delta;
+        let section = bounds.start + offset as u16;
+
+        match runs.last_mut()
+        {
+            Some(run) if run.owners as i64 == owners => run.range.end = section,
+            _ => runs.push(CoverageRun { range: Range::new(section, section), owners: owners as u32 }),
+        }
+    }
+
+    Some(CoverageMap { bounds, runs })
+}
+
+// Any elf left untouched must not share a section with another untouched elf, so at most a maximum set of disjoint
+// assignments can stay as they are.  That set is found greedily by earliest end.  Every other elf is moved into one
+// of the gaps left over, or released.  When there are at least as many moved elves as gaps this is the minimum
+// possible number of changes; otherwise the leftover gaps are absorbed by stretching a neighbouring kept elf.
+pub fn suggest_reassignment(assignments: &[Assignment], bounds: Option<Range<u16>>) -> Vec<Reassignment>
+{
+    let bounds = match bounds.or_else(|| default_bounds(assignments))
+    {
+        Some(bounds) => bounds,
+        None => return Vec::new(),
+    };
+
+    let mut by_end: Vec<usize> = (0..assignments.len()).collect();
+    by_end.sort_by_key(|index| (assignments[*index].range.end, assignments[*index].range.start));
+
+    let mut kept: Vec<usize> = Vec::new();
+    let mut released: Vec<usize> = Vec::new();
+    for index in by_end
+    {
+        let range = assignments[index].range;
+        let fits = range.start >= bounds.start && range.end <= bounds.end;
+        let clear = kept.last().is_none_or(|last| assignments[*last].range.end < range.start);
+
+        if fits && clear { kept.push(index); } else { released.push(index); }
+    }
+
+    // Any released elf that only touches one kept elf, and covers all of it, can take its place without changing the
+    // count and leaves fewer gaps behind.
+    for slot in 0..released.len()
+    {
+        let candidate = assignments[released[slot]].range;
+        if candidate.start < bounds.start || candidate.end > bounds.end
+        {
+            continue;
+        }
+
+        let first = kept.partition_point(|index| assignments[*index].range.end < candidate.start);
+        let touches_next = kept.get(first + 1).is_some_and(|index| assignments[*index].range.start <= candidate.end);
+        if let Some(kept_index) = kept.get(first).copied()
+        {
+            if !touches_next && candidate.contains(&assignments[kept_index].range)
+            {
+                kept[first] = released[slot];
+                released[slot] = kept_index;
+            }
+        }
+    }
+    released.sort();
+
+    let kept_set = IntervalSet::from_ranges(kept.iter().map(|index| assignments[*index].range));
+    let gaps: Vec<Range<u16>> = kept_set.complement(bounds).iter().copied().collect();
+
+    let mut changes = Vec::new();
+    let mut released_iter = released.into_iter();
+    let mut stretched: HashMap<usize, Range<u16>> = HashMap::new();
+
+    for gap in gaps
+    {
+        if let Some(index) = released_iter.next()
+        {
+            changes.push(Reassignment { assignment: index, from: assignments[index].range, to: Some(gap) });
+            continue;
+        }
+
+        // Out of spare elves: stretch whichever kept elf sits just before the gap, or just after it at the very start.
+        let neighbour = kept.iter().rev().find(|index| assignments[**index].range.end < gap.start)
+            .or_else(|| kept.iter().find(|index| assignments[**index].range.start > gap.end));
+
+        if let Some(index) = neighbour
+        {
+            let current = stretched.entry(*index).or_insert(assignments[*index].range);
+            *current = Range::new(current.start.min(gap.start), current.end.max(gap.end));
+        }
+    }
+
+    for index in released_iter
+    {
+        changes.push(Reassignment { assignment: index, from: assignments[index].range, to: None });
+    }
+
+    let mut stretched: Vec<(usize, Range<u16>)> = stretched.into_iter().collect();
+    stretched.sort_by_key(|(index, _)| *index);
+    for (index, to) in stretched
+    {
+        changes.push(Reassignment { assignment: index, from: assignments[index].range, to: Some(to) });
+    }
+
+    changes
+}
+
+pub fn print_coverage(assignments: &[Assignment])
+{
+    let map = match coverage_map(assignments, None)
+    {
+        Some(map) => map,
+        None =>
+        {
+            println!("There are no assignments to map.");
+            return;
+        }
+    };
+
+    println!("Sections {}-{}", map.bounds.start, map.bounds.end);
+    for (owners, sections) in map.histogram()
+    {
+        println!("  {} section(s) owned by {} elves", sections, owners);
+    }
+
+    for gap in map.gaps()
+    {
+        println!("Gap: {}-{}", gap.start, gap.end);
+    }
+    for hot in map.hot_spots(2)
+    {
+        println!("Hot spot: {}-{} ({} owners)", hot.range.start, hot.range.end, hot.owners);
+    }
+
+    let changes = suggest_reassignment(assignments, Some(map.bounds));
+    println!("Suggested reassignments ({}):", changes.len());
+    for change in changes
+    {
+        let assignment = &assignments[change.assignment];
+        match change.to
+        {
+            Some(to) => println!("  line {} elf {}: {}-{} -> {}-{}", assignment.line + 1, assignment.elf + 1, change.from.start, change.from.end, to.start, to.end),
+            None => println!("  line {} elf {}: {}-{} -> released", assignment.line + 1, assignment.elf + 1, change.from.start, change.from.end),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day4::interval::Range;
+    use crate::day4::population::Assignment;
+
+    use super::{coverage_map, suggest_reassignment, CoverageRun};
+
+    fn assignments(ranges: &[(u16, u16)]) -> Vec<Assignment>
+    {
+        ranges.iter().enumerate().map(|(line, (start, end))| Assignment { line, elf: 0, range: Range::new(*start, *end) }).collect()
+    }
+
+    fn owners_after(population: &[Assignment], changes: &[super::Reassignment], section: u16) -> usize
+    {
+        let mut owners = 0;
+        for (index, assignment) in population.iter().enumerate()
+        {
+            let range = match changes.iter().find(|change| change.assignment == index)
+            {
+                Some(change) => change.to,
+                None => Some(assignment.range),
+            };
+            if range.is_some_and(|range| range.contains_point(section)) { owners += 1; }
+        }
+
+        owners
+    }
+
+    #[test]
+    pub fn coverage_map_splits_the_bounds_into_runs_by_owner_count()
+    {
+        let population = assignments(&[(2, 4), (3, 6), (9, 9)]);
+
+        let map = coverage_map(&population, None).unwrap();
+
+        assert_eq!(map.runs, vec![
+            CoverageRun { range: Range::new(2, 2), owners: 1 },
+            CoverageRun { range: Range::new(3, 4), owners: 2 },
+            CoverageRun { range: Range::new(5, 6), owners: 1 },
+            CoverageRun { range: Range::new(7, 8), owners: 0 },
+            CoverageRun { range: Range::new(9, 9), owners: 1 },
+        ]);
+        assert_eq!(map.gaps(), vec![Range::new(7, 8)]);
+        assert_eq!(map.hot_spots(2).len(), 1);
+        assert_eq!(map.histogram().get(&1), Some(&4));
+    }
+
+    #[test]
+    pub fn explicit_bounds_show_gaps_at_the_edges()
+    {
+        let population = assignments(&[(3, 4)]);
+
+        let map = coverage_map(&population, Some(Range::new(1, 6))).unwrap();
+
+        assert_eq!(map.gaps(), vec![Range::new(1, 2), Range::new(5, 6)]);
+        assert!(coverage_map(&[], None).is_none());
+    }
+
+    #[test]
+    pub fn reassignment_moves_overlapping_elves_into_gaps_so_every_section_has_one_owner()
+    {
+        let population = assignments(&[(1, 3), (2, 5), (6, 6), (9, 10), (9, 9)]);
+
+        let changes = suggest_reassignment(&population, None);
+
+        assert_eq!(changes.len(), 2);
+        for section in 1..=10
+        {
+            assert_eq!(owners_after(&population, &changes, section), 1, "section {}", section);
+        }
+    }
+
+    #[test]
+    pub fn reassignment_stretches_kept_elves_when_there_are_more_gaps_than_spare_elves()
+    {
+        let population = assignments(&[(1, 2), (5, 6), (9, 10)]);
+
+        let changes = suggest_reassignment(&population, None);
+
+        assert_eq!(changes.len(), 2);
+        for section in 1..=10
+        {
+            assert_eq!(owners_after(&population, &changes, section), 1, "section {}", section);
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use std::slice::Iter;
+
+use super::advent::SectionRange;
+
+// Values with a well defined neighbour on either side.  Needed so that inclusive ranges such as 1-3 and 4-6 can be
+// recognised as touching, and so a complement can step just past the end of a range.
+pub trait Discrete: Ord + Copy
+{
+    fn next(self) -> Option<Self>;
+
+    fn prev(self) -> Option<Self>;
+
+    // Number of values in start..=end.
+    fn span(start: Self, end: Self) -> u64;
+}
+
+macro_rules! impl_discrete
+{
+    ($($t:ty),*) =>
+    {
+        $(
+            impl Discrete for $t
+            {
+                fn next(self) -> Option<Self> { self.checked_add(1) }
+
+                fn prev(self) -> Option<Self> { self.checked_sub(1) }
+
+                fn span(start: Self, end: Self) -> u64 { (end as i128 - start as i128 + 1) as u64 }
+            }
+        )*
+    };
+}
+
+impl_discrete!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+// An inclusive range, in the same sense as the 2-4 section assignments.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Range<T: Ord>
+{
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Ord + Copy> Range<T>
+{
+    pub fn new(start: T, end: T) -> Range<T>
+    {
+        if start > end
+        {
+            panic!("A range has to start before it ends.");
+        }
+
+        Range { start, end }
+    }
+
+    pub fn contains_point(&self, point: T) -> bool
+    {
+        self.start <= point && point <= self.end
+    }
+
+    pub fn contains(&self, other: &Range<T>) -> bool
+    {
+        self.start <= other.start && self.end >= other.end
+    }
+
+    pub fn overlaps(&self, other: &Range<T>) -> bool
+    {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Range<T>) -> Option<Range<T>>
+    {
+        if !self.overlaps(other)
+        {
+            return None;
+        }
+
+        Some(Range { start: self.start.max(other.start), end: self.end.min(other.end) })
+    }
+}
+
+// An assignment written backwards, like 5-3, still covers the sections between its two ends.
+impl From<&SectionRange> for Range<u16>
+{
+    fn from(section: &SectionRange) -> Self
+    {
+        Range::new(section.start.min(section.end), section.start.max(section.end))
+    }
+}
+
+// A set of values stored as sorted, disjoint, non-touching inclusive ranges.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct IntervalSet<T: Ord>
+{
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Discrete> IntervalSet<T>
+{
+    pub fn new() -> IntervalSet<T>
+    {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    pub fn from_ranges<I>(ranges: I) -> IntervalSet<T>
+    where I: IntoIterator<Item = Range<T>>
+    {
+        let mut sorted: Vec<Range<T>> = ranges.into_iter().collect();
+        sorted.sort_by(|a, b| a.start.cmp(&b.start).then(a.end.cmp(&b.end)));
+
+        let mut normalised: Vec<Range<T>> = Vec::with_capacity(sorted.len());
+        for range in sorted
+        {
+            if let Some(last) = normalised.last_mut()
+            {
+                if range.start <= last.end || last.end.next() == Some(range.start)
+                {
+                    last.end = last.end.max(range.end);
+                    continue;
+                }
+            }
+
+            normalised.push(range);
+        }
+
+        IntervalSet { ranges: normalised }
+    }
+
+    pub fn insert(&mut self, range: Range<T>)
+    {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range);
+        *self = IntervalSet::from_ranges(ranges);
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.ranges.is_empty()
+    }
+
+    // Total number of values covered, not the number of ranges.
+    pub fn len(&self) -> u64
+    {
+        self.ranges.iter().map(|range| T::span(range.start, range.end)).sum()
+    }
+
+    pub fn ranges(&self) -> &[Range<T>]
+    {
+        &self.ranges
+    }
+
+    pub fn iter(&self) -> Iter<'_, Range<T>>
+    {
+        self.ranges.iter()
+    }
+
+    pub fn contains_point(&self, point: T) -> bool
+    {
+        self.ranges.iter().any(|range| range.contains_point(point))
+    }
+
+    pub fn contains(&self, range: &Range<T>) -> bool
+    {
+        self.ranges.iter().any(|own| own.contains(range))
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T>
+    {
+        IntervalSet::from_ranges(self.ranges.iter().chain(other.ranges.iter()).copied())
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T>
+    {
+        let mut result = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len()
+        {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            if let Some(common) = a.intersection(b)
+            {
+                result.push(common);
+            }
+
+            // Whichever range finishes first can't meet anything further along in the other set.
+            if a.end < b.end { i += 1; } else { j += 1; }
+        }
+
+        IntervalSet { ranges: result }
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T>
+    {
+        match (self.ranges.first(), self.ranges.last())
+        {
+            (Some(first), Some(last)) => self.intersection(&other.complement(Range::new(first.start, last.end))),
+            _ => IntervalSet::new(),
+        }
+    }
+
+    pub fn complement(&self, bounds: Range<T>) -> IntervalSet<T>
+    {
+        let mut result = Vec::new();
+        let mut cursor = Some(bounds.start);
+
+        for range in &self.ranges
+        {
+            let from = match cursor
+            {
+                Some(from) => from,
+                None => break,
+            };
+
+            if range.end < from
+            {
+                continue;
+            }
+            if range.start > bounds.end
+            {
+                break;
+            }
+
+            if range.start > from
+            {
+                // range.start > from >= T's minimum, so there is always a predecessor here.
+                result.push(Range::new(from, range.start.prev().unwrap()));
+            }
+
+            cursor = range.end.next();
+        }
+
+        if let Some(from) = cursor
+        {
+            if from <= bounds.end
+            {
+                result.push(Range::new(from, bounds.end));
+            }
+        }
+
+        IntervalSet { ranges: result }
+    }
+}
+
+impl<'a, T: Discrete> IntoIterator for &'a IntervalSet<T>
+{
+    type Item = &'a Range<T>;
+    type IntoIter = Iter<'a, Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter
+    {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use super::{IntervalSet, Range};
+
+    fn set(ranges: &[(u16, u16)]) -> IntervalSet<u16>
+    {
+        IntervalSet::from_ranges(ranges.iter().map(|(start, end)| Range::new(*start, *end)))
+    }
+
+    #[test]
+    pub fn from_ranges_sorts_and_merges_overlapping_and_touching_ranges()
+    {
+        let merged = set(&[(8, 9), (1, 3), (4, 5), (2, 2), (11, 12)]);
+
+        assert_eq!(merged.ranges(), &[Range::new(1, 5), Range::new(8, 9), Range::new(11, 12)]);
+        assert_eq!(merged.len(), 9);
+    }
+
+    #[test]
+    pub fn union_and_intersection_of_two_sets()
+    {
+        let a = set(&[(1, 4), (10, 20)]);
+        let b = set(&[(3, 12), (18, 25)]);
+
+        assert_eq!(a.union(&b), set(&[(1, 25)]));
+        assert_eq!(a.intersection(&b), set(&[(3, 4), (10, 12), (18, 20)]));
+    }
+
+    #[test]
+    pub fn difference_removes_everything_in_the_other_set()
+    {
+        let a = set(&[(1, 10)]);
+        let b = set(&[(3, 4), (8, 20)]);
+
+        assert_eq!(a.difference(&b), set(&[(1, 2), (5, 7)]));
+        assert!(b.difference(&set(&[(0, 100)])).is_empty());
+    }
+
+    #[test]
+    pub fn complement_covers_the_gaps_within_bounds_including_the_type_limits()
+    {
+        let a = set(&[(3, 5), (8, 8)]);
+
+        assert_eq!(a.complement(Range::new(1, 10)), set(&[(1, 2), (6, 7), (9, 10)]));
+        assert_eq!(set(&[(0, 5)]).complement(Range::new(0, u16::MAX)), set(&[(6, u16::MAX)]));
+        assert!(set(&[(0, u16::MAX)]).complement(Range::new(0, u16::MAX)).is_empty());
+    }
+
+    #[test]
+    pub fn iterating_a_set_visits_ranges_in_order()
+    {
+        let a = set(&[(20, 30), (1, 2)]);
+
+        let starts: Vec<u16> = a.iter().map(|range| range.start).collect();
+        assert_eq!(starts, vec![1, 20]);
+        assert!(a.contains(&Range::new(21, 29)));
+        assert!(!a.contains(&Range::new(2, 20)));
+    }
+}
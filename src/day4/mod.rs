@@ -1 +1,4 @@
-pub mod advent;
\ No newline at end of file
+pub mod advent;
+pub mod interval;
+pub mod population;
+pub mod coverage;
\ No newline at end of file
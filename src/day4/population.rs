@@ -0,0 +1,302 @@
+use std::collections::BTreeSet;
+
+use super::advent::construct_range_pair;
+use super::interval::Range;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Assignment
+{
+    // Line number in the input (0 based) and which elf of the pair it belongs to.
+    pub line: usize,
+    pub elf: usize,
+    pub range: Range<u16>,
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct PairReport
+{
+    // Indices into the assignment slice, ordered so that the first starts no later than the second.
+    pub overlapping: Vec<(usize, usize)>,
+    // (container, containee)
+    pub contained: Vec<(usize, usize)>,
+}
+
+pub fn collect_assignments(lines: &[&str]) -> Vec<Assignment>
+{
+    let mut assignments = Vec::new();
+
+    for (line, pair_str) in lines.iter().enumerate()
+    {
+        if pair_str.is_empty()
+        {
+            continue;
+        }
+
+        let (elf_1, elf_2) = construct_range_pair(pair_str);
+        assignments.push(Assignment { line, elf: 0, range: Range::from(&elf_1) });
+        assignments.push(Assignment { line, elf: 1, range: Range::from(&elf_2) });
+    }
+
+    assignments
+}
+
+// Start order, with longer ranges first on a tie so that a container is always seen before what it contains.
+fn sweep_order(assignments: &[Assignment]) -> Vec<usize>
+{
+    let mut order: Vec<usize> = (0..assignments.len()).collect();
+    order.sort_by(|a, b|
+    {
+        let (a, b) = (&assignments[*a].range, &assignments[*b].range);
+        a.start.cmp(&b.start).then(b.end.cmp(&a.end))
+    });
+
+    order
+}
+
+// Sweep line over the starts.  Anything still active when a range starts overlaps it, so the work done is
+// proportional to the number of pairs reported plus the sort.
+pub fn find_overlapping_pairs(assignments: &[Assignment]) -> PairReport
+{
+    let mut report = PairReport::default();
+    let mut active: Vec<usize> = Vec::new();
+
+    for current in sweep_order(assignments)
+    {
+        let range = assignments[current].range;
+        active.retain(|other| assignments[*other].range.end >= range.start);
+
+        for other in &active
+        {
+            report.overlapping.push((*other, current));
+            if assignments[*other].range.end >= range.end
+            {
+                report.contained.push((*other, current));
+            }
+        }
+
+        active.push(current);
+    }
+
+    report
+}
+
+// Counts (contained, overlapping) pairs without listing them, in O(n log n).  For a million assignments the
+// pair lists themselves can run into the billions, so this is the mode to use when only the totals matter.
+pub fn count_pairs(assignments: &[Assignment]) -> (u64, u64)
+{
+    let n = assignments.len() as u64;
+    let total_pairs = n * n.saturating_sub(1) / 2;
+
+    // Two ranges miss each other exactly when one ends before the other starts.
+    let mut ends: Vec<u16> = assignments.iter().map(|a| a.range.end).collect();
+    ends.sort_unstable();
+    let mut disjoint: u64 = 0;
+    for assignment in assignments
+    {
+        disjoint += ends.partition_point(|end| *end < assignment.range.start) as u64;
+    }
+
+    // A range is contained by every earlier range in sweep order that ends at or after it does.
+    let mut tree = FenwickTree::new(u16::MAX as usize + 1);
+    let mut contained: u64 = 0;
+    for index in sweep_order(assignments)
+    {
+        let end = assignments[index].range.end as usize;
+        contained += tree.count_at_least(end);
+        tree.add(end);
+    }
+
+    (contained, total_pairs - disjoint)
+}
+
+// Maximal groups of assignments that all overlap each other.  Ranges that pairwise overlap always share a common
+// section, so every such group is the set of ranges active at some point of the sweep, just before one of them ends.
+pub fn overlap_clusters(assignments: &[Assignment]) -> Vec<Vec<usize>>
+{
+    // (position, is_end, index).  Starts sort before ends at the same position since ranges are inclusive.
+    let mut events: Vec<(u16, bool, usize)> = Vec::with_capacity(assignments.len() * 2);
+    for (index, assignment) in assignments.iter().enumerate()
+    {
+        events.push((assignment.range.start, false, index));
+        events.push((assignment.range.end, true, index));
+    }
+    events.sort_unstable();
+
+    let mut clusters = Vec::new();
+    let mut active = BTreeSet::<usize>::new();
+    let mut grown = false;
+
+    for (_, is_end, index) in events
+    {
+        if is_end
+        {
+            if grown && active.len() > 1
+            {
+                clusters.push(active.iter().copied().collect());
+            }
+            grown = false;
+            active.remove(&index);
+        }
+        else
+        {
+            active.insert(index);
+            grown = true;
+        }
+    }
+
+    clusters
+}
+
+struct FenwickTree
+{
+    counts: Vec<u64>,
+    total: u64,
+}
+
+impl FenwickTree
+{
+    fn new(size: usize) -> FenwickTree
+    {
+        FenwickTree { counts: vec![0; size + 1], total: 0 }
+    }
+
+    fn add(&mut self, position: usize)
+    {
+        self.total += 1;
+        let mut i = position + 1;
+        while i < self.counts.len()
+        {
+            self.counts[i] += 1;
+            i += i & i.wrapping_neg();
+        }
+    }
+
+    fn count_below(&self, position: usize) -> u64
+    {
+        let mut sum = 0;
+        let mut i = position;
+        while i > 0
+        {
+            sum += self.counts[i];
+            i -= i & i.wrapping_neg();
+        }
+
+        sum
+    }
+
+    fn count_at_least(&self, position: usize) -> u64
+    {
+        self.total - self.count_below(position)
+    }
+}
+
+pub fn print_population(lines: &[&str])
+{
+    let assignments = collect_assignments(lines);
+    let (contained, overlapping) = count_pairs(&assignments);
+
+    println!("{} assignments, {} overlapping pairs, {} fully contained pairs", assignments.len(), overlapping, contained);
+
+    for cluster in overlap_clusters(&assignments)
+    {
+        let members: Vec<String> = cluster.iter().map(|index|
+        {
+            let assignment = &assignments[*index];
+            format!("line {} elf {} ({}-{})", assignment.line + 1, assignment.elf + 1, assignment.range.start, assignment.range.end)
+        }).collect();
+        println!("Cluster of {}: {}", cluster.len(), members.join(", "));
+    }
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day4::interval::Range;
+
+    use super::{collect_assignments, count_pairs, find_overlapping_pairs, overlap_clusters, Assignment};
+
+    fn assignments(ranges: &[(u16, u16)]) -> Vec<Assignment>
+    {
+        ranges.iter().enumerate().map(|(line, (start, end))| Assignment { line, elf: 0, range: Range::new(*start, *end) }).collect()
+    }
+
+    fn pseudo_random(count: usize) -> Vec<Assignment>
+    {
+        let mut state: u64 = 12345;
+        let mut ranges = Vec::new();
+        for _ in 0..count
+        {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            let start = ((state >> 33) % 90) as u16;
+            let length = ((state >> 50) % 12) as u16;
+            ranges.push((start, start + length));
+        }
+
+        assignments(&ranges)
+    }
+
+    #[test]
+    pub fn collect_assignments_takes_both_elves_from_every_line()
+    {
+        let found = collect_assignments(&["2-4,6-8", "", "2-3,4-5"]);
+
+        assert_eq!(found.len(), 4);
+        assert_eq!(found[3], Assignment { line: 2, elf: 1, range: Range::new(4, 5) });
+    }
+
+    #[test]
+    pub fn the_sweep_agrees_with_comparing_every_pair()
+    {
+        let population = pseudo_random(400);
+
+        let mut overlapping = 0u64;
+        let mut contained = 0u64;
+        for i in 0..population.len()
+        {
+            for j in (i + 1)..population.len()
+            {
+                let (a, b) = (&population[i].range, &population[j].range);
+                if a.overlaps(b) { overlapping += 1; }
+                if a.contains(b) || b.contains(a) { contained += 1; }
+            }
+        }
+
+        let report = find_overlapping_pairs(&population);
+        assert_eq!(report.overlapping.len() as u64, overlapping);
+        assert_eq!(report.contained.len() as u64, contained);
+        assert_eq!(count_pairs(&population), (contained, overlapping));
+        for (container, containee) in report.contained
+        {
+            assert!(population[container].range.contains(&population[containee].range));
+        }
+    }
+
+    #[test]
+    pub fn identical_ranges_are_one_contained_pair()
+    {
+        let population = assignments(&[(3, 7), (3, 7)]);
+
+        assert_eq!(find_overlapping_pairs(&population).contained, vec![(0, 1)]);
+        assert_eq!(count_pairs(&population), (1, 1));
+    }
+
+    #[test]
+    pub fn clusters_are_the_maximal_sets_sharing_a_section()
+    {
+        // 0 and 1 share 4-5, 1 and 2 share 8, but 0 and 2 never meet.
+        let population = assignments(&[(1, 5), (4, 8), (8, 10), (20, 21)]);
+
+        assert_eq!(overlap_clusters(&population), vec![vec![0, 1], vec![1, 2]]);
+    }
+
+    #[test]
+    pub fn count_pairs_handles_a_large_population()
+    {
+        let population = pseudo_random(200_000);
+
+        let (contained, overlapping) = count_pairs(&population);
+
+        assert!(contained <= overlapping);
+        assert!(overlapping > 0);
+    }
+}
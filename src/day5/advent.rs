@@ -2,71 +2,72 @@ use std::{cell::RefCell};
 
 use log::debug;
 
+use super::crane::{Crane, CrateMover9000, CrateMover9001};
 
-pub fn solver(lines: Vec<&str>) -> String
+
+pub fn solver(lines: Vec<&str>) -> (String, String)
 {
-    let (mut setup, mut operations) = input_processor(lines);
+    // Both cranes start from the same drawing, so each one gets its own copy of the input.
+    let old_crane = solve_with_crane(lines.clone(), &CrateMover9000);
+    let new_crane = solve_with_crane(lines, &CrateMover9001);
 
-    // setup's top line is just the numbers at the base of every stack.  pop it and use it to initialize the cargo stack.
-    let stacks_width = stack_width(setup.pop().unwrap());
-    let cargo_stacks = RefCell::new(Vec::<Vec<&str>>::new());
+    (old_crane, new_crane)
+}
 
-    {
-        let mut mut_cargo_stacks = cargo_stacks.borrow_mut();
-        for _i in 0..stacks_width
-        {
-            mut_cargo_stacks.push(Vec::<&str>::new())
-        }
-    }
+pub fn solve_with_crane(lines: Vec<&str>, crane: &dyn Crane) -> String
+{
+    let (setup, mut operations) = input_processor(lines);
 
-    while !setup.is_empty() {stackifier(&cargo_stacks, columnizer(setup.pop().unwrap()))}
+    let cargo_stacks = build_stacks(setup);
 
+    debug!("Moving crates with the {}", crane.name());
     while !operations.is_empty()
     {
         let operation = operations.remove(0);
         let (count, from, to) = translate_operation(operation);
-        move_crates(&cargo_stacks, count, from, to);
+        crane.move_crates(&cargo_stacks, count, from, to);
     }
 
-    return get_final_state(&cargo_stacks);
+    get_final_state(&cargo_stacks)
 }
 
-fn get_final_state(cargo_stacks: &RefCell<Vec<Vec<&str>>>) -> String
+pub fn build_stacks(mut setup: Vec<&str>) -> RefCell<Vec<Vec<&str>>>
 {
-    let k = cargo_stacks.borrow();
-    let mut partial = String::from("");
-    for stack in k.as_slice()
+    // setup's top line is just the numbers at the base of every stack.  pop it and use the position of every label
+    // to work out where each stack sits in the rows above.
+    let base = setup.pop().unwrap();
+    let columns = locate_columns(base);
+    if stack_width(base) as usize != columns.len()
     {
-        partial.push_str(*stack.get(stack.len() - 1).unwrap());
+        panic!("The base row {} should number its stacks 1 through {}.", base.trim(), columns.len());
     }
 
-    return partial;
-}
+    let cargo_stacks = RefCell::new(Vec::<Vec<&str>>::new());
 
-fn move_crates(cargo_stacks: &RefCell<Vec<Vec<&str>>>, count: u64, from: usize, to: usize)
-{
     {
-        debug!("Cargo stacks is {} stacks wide.", cargo_stacks.borrow().len());
-        debug!("Attempting to add {} crate(s) to {}, from {}", count, to, from);
+        let mut mut_cargo_stacks = cargo_stacks.borrow_mut();
+        for _i in 0..columns.len()
+        {
+            mut_cargo_stacks.push(Vec::<&str>::new())
+        }
     }
-    let mut mut_cargo_stacks = cargo_stacks.borrow_mut();
 
-    let cargo_from = mut_cargo_stacks.get_mut(from).unwrap();
-    let mut temp = Vec::<&str>::new();
-    for _i in 0..count
-    {
-        temp.push(cargo_from.pop().unwrap());
-        debug!("Removed crate {} from the stack.", temp.get(temp.len() - 1).unwrap());
-    }
+    while !setup.is_empty() {stackifier(&cargo_stacks, columnize_by_position(setup.pop().unwrap(), &columns))}
 
+    cargo_stacks
+}
 
-    let cargo_to = mut_cargo_stacks.get_mut(to).unwrap();
-    while !temp.is_empty()
+pub fn get_final_state(cargo_stacks: &RefCell<Vec<Vec<&str>>>) -> String
+{
+    let k = cargo_stacks.borrow();
+    let mut partial = String::from("");
+    for stack in k.as_slice()
     {
-        // cargo_to.push(temp.remove(0));
-        cargo_to.push(temp.pop().unwrap());
+        // An emptied stack still holds its place in the answer.
+        partial.push_str(stack.last().unwrap_or(&" "));
     }
-    
+
+    return partial;
 }
 
 fn stack_width(counter: &str) -> u64
@@ -122,7 +123,7 @@ pub fn input_processor(lines: Vec<&str>) -> (Vec<&str>, Vec<&str>)
     return (setup, commands);
 }
 
-pub fn stackifier<'a>(stack_set: &'a RefCell<Vec<Vec<&'a str>>>, new_row: Vec<&'a str>)
+pub fn stackifier<'a>(stack_set: &RefCell<Vec<Vec<&'a str>>>, new_row: Vec<&'a str>)
 {
     let mut mut_stack_set = stack_set.borrow_mut();
     if mut_stack_set.len() != new_row.len()
@@ -164,6 +165,88 @@ pub fn stackifier<'a>(stack_set: &'a RefCell<Vec<Vec<&'a str>>>, new_row: Vec<&'
     }
 }
 
+// Byte span (start inclusive, end exclusive) of every label in the numbered base row.
+pub fn locate_columns(base: &str) -> Vec<(usize, usize)>
+{
+    let mut columns = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (position, character) in base.char_indices()
+    {
+        match (character.is_whitespace(), start)
+        {
+            (false, None) => start = Some(position),
+            (true, Some(label_start)) =>
+            {
+                columns.push((label_start, position));
+                start = None;
+            },
+            _ => {},
+        }
+    }
+
+    if let Some(label_start) = start
+    {
+        columns.push((label_start, base.len()));
+    }
+
+    columns
+}
+
+// Places every [label] in the row under whichever base label its center is closest to, so labels can be any width
+// and trailing whitespace can be missing or ragged.
+pub fn columnize_by_position<'a>(row_str: &'a str, columns: &[(usize, usize)]) -> Vec<&'a str>
+{
+    let mut column_entries = vec![""; columns.len()];
+    let mut rest = row_str;
+    let mut offset = 0;
+
+    while let Some(open) = rest.find('[')
+    {
+        if !rest[..open].trim().is_empty()
+        {
+            panic!("Found {} outside of a crate in row {}.", rest[..open].trim(), row_str);
+        }
+
+        let close = match rest[open..].find(']')
+        {
+            Some(close) => open + close,
+            None => panic!("A crate in row {} is missing its closing bracket.", row_str),
+        };
+
+        let label = rest[open + 1..close].trim();
+        // Doubled centers keep everything in integers.
+        let center = 2 * (offset + open) + close - open;
+
+        let mut nearest = 0;
+        for (index, (start, end)) in columns.iter().enumerate()
+        {
+            let distance = (start + end - 1).abs_diff(center);
+            if distance < (columns[nearest].0 + columns[nearest].1 - 1).abs_diff(center)
+            {
+                nearest = index;
+            }
+        }
+
+        debug!("Crate {} at offset {} belongs to stack {}", label, offset + open, nearest + 1);
+        if !column_entries[nearest].is_empty()
+        {
+            panic!("Two crates in row {} sit over stack {}.", row_str, nearest + 1);
+        }
+        column_entries[nearest] = label;
+
+        offset += close + 1;
+        rest = &rest[close + 1..];
+    }
+
+    if !rest.trim().is_empty()
+    {
+        panic!("Found {} outside of a crate in row {}.", rest.trim(), row_str);
+    }
+
+    column_entries
+}
+
 pub fn columnizer(row_str: &str) -> Vec<&str>
 {
     let mut column_entries = Vec::new();
@@ -209,7 +292,7 @@ pub mod tests
 {
     use std::cell;
 
-    use crate::day5::advent::{columnizer, stackifier, input_processor};
+    use crate::day5::advent::{build_stacks, columnize_by_position, columnizer, locate_columns, stackifier, input_processor};
 
     use super::{stack_width, translate_operation};
 
@@ -371,4 +454,51 @@ pub mod tests
         assert_eq!(columns.get(6).unwrap(), &"E");
 
     }
+
+    #[test]
+    pub fn locate_columns_finds_the_span_of_every_base_label_including_multi_digit_ones()
+    {
+        let base = " 1   2   3   4   5   6   7   8   9  10  11 ";
+
+        let columns = locate_columns(base);
+
+        assert_eq!(columns.len(), 11);
+        assert_eq!(columns[0], (1, 2));
+        assert_eq!(columns[9], (36, 38));
+        assert_eq!(columns[10], (40, 42));
+    }
+
+    #[test]
+    pub fn columnize_by_position_accepts_trimmed_and_ragged_trailing_whitespace()
+    {
+        let columns = locate_columns(" 1   2   3 ");
+
+        assert_eq!(columnize_by_position("    [D]", &columns), vec!["", "D", ""]);
+        assert_eq!(columnize_by_position("    [D]        ", &columns), vec!["", "D", ""]);
+        assert_eq!(columnize_by_position("", &columns), vec!["", "", ""]);
+    }
+
+    #[test]
+    pub fn columnize_by_position_handles_multi_character_labels()
+    {
+        let columns = locate_columns("  1     2     3  ");
+
+        assert_eq!(columnize_by_position("[AB]        [CDE]", &columns), vec!["AB", "", "CDE"]);
+    }
+
+    #[test]
+    pub fn build_stacks_reads_eleven_stacks_from_a_base_row_with_two_digit_labels()
+    {
+        let setup = vec![
+            "                                        [K]",
+            "[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [L]",
+            " 1   2   3   4   5   6   7   8   9  10  11 ",
+        ];
+
+        let stacks = build_stacks(setup);
+
+        assert_eq!(stacks.borrow().len(), 11);
+        assert_eq!(stacks.borrow()[9], vec!["J"]);
+        assert_eq!(stacks.borrow()[10], vec!["L", "K"]);
+    }
 }
\ No newline at end of file
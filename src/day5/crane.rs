@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+
+use log::debug;
+
+pub trait Crane
+{
+    fn name(&self) -> &'static str;
+
+    fn move_crates(&self, cargo_stacks: &RefCell<Vec<Vec<&str>>>, count: u64, from: usize, to: usize);
+}
+
+// Lifts one crate at a time, so a multi-crate move lands in reverse order.
+pub struct CrateMover9000;
+
+// Lifts the whole run of crates at once, so their order is preserved.
+pub struct CrateMover9001;
+
+impl Crane for CrateMover9000
+{
+    fn name(&self) -> &'static str
+    {
+        "CrateMover 9000"
+    }
+
+    fn move_crates(&self, cargo_stacks: &RefCell<Vec<Vec<&str>>>, count: u64, from: usize, to: usize)
+    {
+        let mut mut_cargo_stacks = cargo_stacks.borrow_mut();
+
+        for _i in 0..count
+        {
+            let lifted = mut_cargo_stacks.get_mut(from).unwrap().pop().unwrap();
+            debug!("Moved crate {} from {} to {}", lifted, from, to);
+            mut_cargo_stacks.get_mut(to).unwrap().push(lifted);
+        }
+    }
+}
+
+impl Crane for CrateMover9001
+{
+    fn name(&self) -> &'static str
+    {
+        "CrateMover 9001"
+    }
+
+    fn move_crates(&self, cargo_stacks: &RefCell<Vec<Vec<&str>>>, count: u64, from: usize, to: usize)
+    {
+        let mut mut_cargo_stacks = cargo_stacks.borrow_mut();
+
+        let cargo_from = mut_cargo_stacks.get_mut(from).unwrap();
+        let lifted = cargo_from.split_off(cargo_from.len() - count as usize);
+        debug!("Moved crates {:?} from {} to {}", lifted, from, to);
+
+        mut_cargo_stacks.get_mut(to).unwrap().extend(lifted);
+    }
+}
+
+pub fn crane_by_model(model: &str) -> Option<Box<dyn Crane>>
+{
+    match model
+    {
+        "9000" => Some(Box::new(CrateMover9000)),
+        "9001" => Some(Box::new(CrateMover9001)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use std::cell::RefCell;
+
+    use super::{Crane, CrateMover9000, CrateMover9001, crane_by_model};
+
+    fn stacks<'a>() -> RefCell<Vec<Vec<&'a str>>>
+    {
+        RefCell::new(vec![vec!["A", "B", "C"], vec!["D"]])
+    }
+
+    #[test]
+    pub fn the_9000_moves_crates_one_at_a_time_reversing_their_order()
+    {
+        let cargo = stacks();
+
+        CrateMover9000.move_crates(&cargo, 2, 0, 1);
+
+        assert_eq!(*cargo.borrow(), vec![vec!["A"], vec!["D", "C", "B"]]);
+    }
+
+    #[test]
+    pub fn the_9001_moves_crates_all_at_once_keeping_their_order()
+    {
+        let cargo = stacks();
+
+        CrateMover9001.move_crates(&cargo, 2, 0, 1);
+
+        assert_eq!(*cargo.borrow(), vec![vec!["A"], vec!["D", "B", "C"]]);
+    }
+
+    #[test]
+    pub fn crane_by_model_knows_both_models()
+    {
+        assert_eq!(crane_by_model("9000").unwrap().name(), "CrateMover 9000");
+        assert_eq!(crane_by_model("9001").unwrap().name(), "CrateMover 9001");
+        assert!(crane_by_model("9002").is_none());
+    }
+}
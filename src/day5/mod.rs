@@ -1 +1,6 @@
-pub mod advent;
\ No newline at end of file
+pub mod advent;
+pub mod crane;
+pub mod render;
+pub mod validate;
+pub mod replay;
+pub mod planner;
\ No newline at end of file
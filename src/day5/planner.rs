@@ -0,0 +1,212 @@
+use std::cell::RefCell;
+use std::collections::{HashSet, VecDeque};
+
+use log::debug;
+
+use super::crane::Crane;
+use super::render::render_stacks;
+
+pub enum Target<'a>
+{
+    // Every stack must hold exactly these crates, bottom first.
+    Drawing(Vec<Vec<&'a str>>),
+    // Only the top crate of every stack matters, written the way get_final_state writes it: " " for an empty stack.
+    Tops(Vec<&'a str>),
+}
+
+impl<'a> Target<'a>
+{
+    fn reached(&self, stacks: &[Vec<&str>]) -> bool
+    {
+        match self
+        {
+            Target::Drawing(drawing) => drawing.as_slice() == stacks,
+            Target::Tops(tops) => tops.len() == stacks.len() && tops.iter().zip(stacks).all(|(top, stack)| *stack.last().unwrap_or(&" ") == *top),
+        }
+    }
+
+    // Cheap checks that rule a target out before searching: no crane adds, removes or relabels crates.
+    fn reachable_from(&self, start: &[Vec<&str>]) -> bool
+    {
+        let mut crates: Vec<&str> = start.iter().flatten().copied().collect();
+        crates.sort();
+
+        match self
+        {
+            Target::Drawing(drawing) =>
+            {
+                let mut wanted: Vec<&str> = drawing.iter().flatten().copied().collect();
+                wanted.sort();
+                drawing.len() == start.len() && wanted == crates
+            },
+            Target::Tops(tops) =>
+            {
+                let mut wanted: Vec<&str> = tops.iter().filter(|top| **top != " ").copied().collect();
+                wanted.sort();
+                tops.len() == start.len() && wanted.iter().all(|top| wanted.iter().filter(|other| *other == top).count() <= crates.iter().filter(|other| *other == top).count())
+            },
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PlanError
+{
+    // No sequence of moves gets there at all.
+    Unreachable,
+    // Every one of limit states was looked at without finding the target, so it may or may not be reachable.
+    LimitReached { limit: usize },
+}
+
+struct Node<'a>
+{
+    stacks: Vec<Vec<&'a str>>,
+    // Index of the node this one was reached from, and the move that got here.
+    parent: Option<(usize, String)>,
+}
+
+// Breadth first search over stack states, one edge per "move N from A to B" the crane can make, so the first state
+// that reaches the target comes with a shortest possible program.  Gives up if the target is unreachable or more than
+// limit states have to be looked at, and says which.
+pub fn plan<'a>(start: &[Vec<&'a str>], target: &Target, crane: &dyn Crane, limit: usize) -> Result<Vec<String>, PlanError>
+{
+    if !target.reachable_from(start)
+    {
+        return Err(PlanError::Unreachable);
+    }
+
+    let mut nodes = vec![Node { stacks: start.to_vec(), parent: None }];
+    let mut seen: HashSet<Vec<Vec<&'a str>>> = HashSet::new();
+    seen.insert(start.to_vec());
+    let mut queue = VecDeque::from([0]);
+
+    while let Some(current) = queue.pop_front()
+    {
+        if target.reached(&nodes[current].stacks)
+        {
+            debug!("Found a plan after looking at {} states", nodes.len());
+            return Ok(unwind(&nodes, current));
+        }
+
+        for from in 0..start.len()
+        {
+            for to in (0..start.len()).filter(|to| *to != from)
+            {
+                for count in 1..=nodes[current].stacks[from].len()
+                {
+                    let cargo_stacks = RefCell::new(nodes[current].stacks.clone());
+                    crane.move_crates(&cargo_stacks, count as u64, from, to);
+                    let stacks = cargo_stacks.into_inner();
+
+                    if seen.contains(&stacks)
+                    {
+                        continue;
+                    }
+                    if seen.len() >= limit
+                    {
+                        debug!("Gave up after {} states", seen.len());
+                        return Err(PlanError::LimitReached { limit });
+                    }
+
+                    seen.insert(stacks.clone());
+                    let operation = format!("move {} from {} to {}", count, from + 1, to + 1);
+                    nodes.push(Node { stacks, parent: Some((current, operation)) });
+                    queue.push_back(nodes.len() - 1);
+                }
+            }
+        }
+    }
+
+    // Every state the crane can get to has been looked at.
+    Err(PlanError::Unreachable)
+}
+
+fn unwind(nodes: &[Node], mut current: usize) -> Vec<String>
+{
+    let mut operations = Vec::new();
+    while let Some((parent, operation)) = &nodes[current].parent
+    {
+        operations.push(operation.clone());
+        current = *parent;
+    }
+
+    operations.reverse();
+    operations
+}
+
+// Lays a plan out as a complete puzzle input - starting drawing, blank line, moves - so solver can check it.
+pub fn program(start: &[Vec<&str>], operations: &[String]) -> Vec<String>
+{
+    let mut lines = render_stacks(start);
+    lines.push(String::new());
+    lines.extend(operations.iter().cloned());
+
+    lines
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day5::advent::{build_stacks, solve_with_crane};
+    use crate::day5::crane::{CrateMover9000, CrateMover9001};
+
+    use super::{plan, program, PlanError, Target};
+
+    fn start<'a>() -> Vec<Vec<&'a str>>
+    {
+        build_stacks(vec!["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "]).into_inner()
+    }
+
+    #[test]
+    pub fn a_planned_program_is_no_longer_than_the_one_in_the_puzzle_and_solves_to_the_target()
+    {
+        let start = start();
+        let target = Target::Drawing(vec![vec!["C"], vec!["M"], vec!["P", "D", "N", "Z"]]);
+
+        let operations = plan(&start, &target, &CrateMover9000, 100_000).unwrap();
+        let lines = program(&start, &operations);
+
+        assert!(operations.len() <= 4);
+        assert_eq!(solve_with_crane(lines.iter().map(|line| line.as_str()).collect(), &CrateMover9000), "CMZ");
+    }
+
+    #[test]
+    pub fn the_9001_can_move_a_whole_stack_in_one_go()
+    {
+        let start = start();
+        let target = Target::Drawing(vec![vec!["Z", "N"], vec![], vec!["P", "M", "C", "D"]]);
+
+        assert_eq!(plan(&start, &target, &CrateMover9001, 100_000).unwrap(), vec!["move 3 from 2 to 3"]);
+        assert_eq!(plan(&start, &target, &CrateMover9000, 100_000).unwrap().len(), 2);
+    }
+
+    #[test]
+    pub fn planning_for_tops_only_checks_the_top_crates()
+    {
+        let start = start();
+
+        assert_eq!(plan(&start, &Target::Tops(vec!["N", "D", "P"]), &CrateMover9000, 100_000).unwrap().len(), 0);
+        assert_eq!(plan(&start, &Target::Tops(vec!["D", "C", "P"]), &CrateMover9000, 100_000).unwrap(), vec!["move 1 from 2 to 1"]);
+    }
+
+    #[test]
+    pub fn targets_with_different_crates_are_rejected_without_a_search()
+    {
+        let start = start();
+
+        assert_eq!(plan(&start, &Target::Drawing(vec![vec!["Q"], vec![], vec![]]), &CrateMover9000, 100_000), Err(PlanError::Unreachable));
+        assert_eq!(plan(&start, &Target::Tops(vec!["D", "D", " "]), &CrateMover9000, 100_000), Err(PlanError::Unreachable));
+    }
+
+    #[test]
+    pub fn running_out_of_states_is_told_apart_from_an_unreachable_target()
+    {
+        let start = start();
+        let target = Target::Drawing(vec![vec!["C"], vec!["M"], vec!["P", "D", "N", "Z"]]);
+        assert_eq!(plan(&start, &target, &CrateMover9000, 2), Err(PlanError::LimitReached { limit: 2 }));
+
+        // A lone stack has nowhere to move a crate to, so the search runs out of states rather than hitting the limit.
+        let lone = build_stacks(vec!["[A]", "[B]", " 1 "]).into_inner();
+        assert_eq!(plan(&lone, &Target::Drawing(vec![vec!["A", "B"]]), &CrateMover9001, 100_000), Err(PlanError::Unreachable));
+    }
+}
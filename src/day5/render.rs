@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+
+// Writes a stack state back out as the same drawing that the day 5 parser reads in: one row per level, top level
+// first, every column the same width and separated by a single space, finished with the numbered base.  Columns are
+// three characters wide unless a crate label needs more room.  A stack number as wide as its column, like 10 under a
+// three wide column, sits flush left the way the puzzle draws it; only a number wider than that widens the columns.
+pub fn render_stacks(stacks: &[Vec<&str>]) -> Vec<String>
+{
+    render_highlighted(stacks, &[])
+}
+
+// Same drawing as render_stacks, but the crates at the given (stack index, level) positions are drawn as {X} instead
+// of [X] so they stand out.
+pub fn render_highlighted(stacks: &[Vec<&str>], highlighted: &[(usize, usize)]) -> Vec<String>
+{
+    let widest_label = stacks.iter().flatten().map(|label| label.len()).max().unwrap_or(1);
+    let widest_number = stacks.len().to_string().len();
+    let width = usize::max(widest_label + 2, widest_number);
+
+    let height = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+    let mut rows = Vec::with_capacity(height + 1);
+
+    for level in (0..height).rev()
+    {
+        let cells: Vec<String> = stacks.iter().enumerate().map(|(index, stack)|
+        {
+            match stack.get(level)
+            {
+                Some(label) if highlighted.contains(&(index, level)) => format!("{:<width$}", format!("{{{}}}", label), width = width),
+                Some(label) => format!("{:<width$}", format!("[{}]", label), width = width),
+                None => " ".repeat(width),
+            }
+        }).collect();
+
+        rows.push(cells.join(" "));
+    }
+
+    let base: Vec<String> = (1..=stacks.len()).map(|number|
+    {
+        let digits = number.to_string();
+        let left = (width - digits.len()) / 2;
+        format!("{}{}{}", " ".repeat(left), digits, " ".repeat(width - left - digits.len()))
+    }).collect();
+    rows.push(base.join(" "));
+
+    rows
+}
+
+pub fn render_drawing(stacks: &[Vec<&str>]) -> String
+{
+    render_stacks(stacks).join("\n")
+}
+
+pub fn snapshot(cargo_stacks: &RefCell<Vec<Vec<&str>>>) -> String
+{
+    render_drawing(&cargo_stacks.borrow())
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day5::advent::build_stacks;
+    use crate::day5::crane::{Crane, CrateMover9000};
+
+    use super::{render_drawing, render_stacks, snapshot};
+
+    #[test]
+    pub fn rendering_a_parsed_drawing_gives_back_the_same_bytes()
+    {
+        let drawing = vec!["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "];
+
+        let stacks = build_stacks(drawing.clone());
+
+        assert_eq!(render_stacks(&stacks.borrow()), drawing);
+    }
+
+    #[test]
+    pub fn rendering_a_taller_drawing_with_gaps_round_trips()
+    {
+        let drawing = vec![
+            "[G]                 [D] [R]        ",
+            "[W]         [V]     [C] [T] [M]    ",
+            "[L]         [P] [Z] [Q] [F] [V]    ",
+            "[J]         [S] [D] [J] [M] [T] [V]",
+            " 1   2   3   4   5   6   7   8   9 ",
+        ];
+
+        let stacks = build_stacks(drawing.clone());
+
+        assert_eq!(render_drawing(&stacks.borrow()), drawing.join("\n"));
+    }
+
+    #[test]
+    pub fn snapshots_show_intermediate_states()
+    {
+        let stacks = build_stacks(vec!["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "]);
+
+        CrateMover9000.move_crates(&stacks, 1, 1, 0);
+
+        assert_eq!(snapshot(&stacks), "[D]        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ");
+    }
+
+    #[test]
+    pub fn empty_stacks_render_only_the_base()
+    {
+        let stacks: Vec<Vec<&str>> = vec![Vec::new(), Vec::new()];
+
+        assert_eq!(render_drawing(&stacks), " 1   2 ");
+    }
+
+    #[test]
+    pub fn a_parsed_drawing_with_ten_or_more_stacks_renders_back_byte_for_byte()
+    {
+        let drawing = vec![
+            "                                        [K]",
+            "[A] [B] [C] [D] [E] [F] [G] [H] [I] [J] [L]",
+            " 1   2   3   4   5   6   7   8   9  10  11 ",
+        ];
+
+        let stacks = build_stacks(drawing.clone());
+
+        assert_eq!(render_stacks(&stacks.borrow()), drawing);
+    }
+
+    #[test]
+    pub fn wide_labels_and_ten_or_more_stacks_round_trip_through_the_parser()
+    {
+        let stacks: Vec<Vec<&str>> = vec![vec!["AB"], vec![], vec!["C", "DEF"], vec![], vec![], vec![], vec![], vec![], vec![], vec!["X"]];
+
+        let drawing = render_stacks(&stacks);
+        let lines: Vec<&str> = drawing.iter().map(|line| line.as_str()).collect();
+        let parsed = build_stacks(lines);
+
+        assert_eq!(*parsed.borrow(), stacks);
+        assert_eq!(drawing.last().unwrap(), "  1     2     3     4     5     6     7     8     9    10  ");
+    }
+}
\ No newline at end of file
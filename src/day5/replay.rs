@@ -0,0 +1,197 @@
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use super::advent::{build_stacks, input_processor};
+use super::crane::Crane;
+use super::render::render_highlighted;
+use super::validate::{check_move, parse_operation, MoveDiagnostic};
+
+pub struct Frame<'a>
+{
+    // 0 is the starting drawing, k is the state after the k-th move.
+    pub step: usize,
+    pub operation: Option<&'a str>,
+    pub stacks: Vec<Vec<&'a str>>,
+    // (stack index, level) of every crate the last move put down.
+    pub moved: Vec<(usize, usize)>,
+}
+
+pub struct Replay<'a>
+{
+    pub crane: &'static str,
+    frames: Vec<Frame<'a>>,
+}
+
+impl<'a> Replay<'a>
+{
+    // A program with a bad move in it cannot be replayed, and fails with the same diagnostic validate_program gives
+    // for its first bad move.
+    pub fn record(lines: Vec<&'a str>, crane: &dyn Crane) -> Result<Replay<'a>, MoveDiagnostic>
+    {
+        let (setup, operations) = input_processor(lines);
+        // Operations start after the drawing and the blank line that follows it.
+        let first_line = setup.len() + 2;
+        let cargo_stacks = build_stacks(setup);
+
+        let mut frames = vec![Frame { step: 0, operation: None, stacks: cargo_stacks.borrow().clone(), moved: Vec::new() }];
+
+        for (index, operation) in operations.into_iter().enumerate()
+        {
+            let heights: Vec<usize> = cargo_stacks.borrow().iter().map(|stack| stack.len()).collect();
+            let checked = parse_operation(operation).and_then(|(count, from, to)| check_move(&heights, count, from, to).map(|indices| (count, indices)));
+
+            let (count, (from, to)) = match checked
+            {
+                Ok(checked) => checked,
+                Err(fault) => return Err(MoveDiagnostic { line: first_line + index, operation: operation.to_string(), fault, heights }),
+            };
+            crane.move_crates(&cargo_stacks, count, from, to);
+
+            let stacks = cargo_stacks.borrow().clone();
+            let height = stacks[to].len();
+            let moved = (height - count as usize..height).map(|level| (to, level)).collect();
+
+            frames.push(Frame { step: index + 1, operation: Some(operation), stacks, moved });
+        }
+
+        Ok(Replay { crane: crane.name(), frames })
+    }
+
+    // Number of moves, so valid steps run from 0 to moves() inclusive.
+    pub fn moves(&self) -> usize
+    {
+        self.frames.len() - 1
+    }
+
+    pub fn frame(&self, step: usize) -> Option<&Frame<'a>>
+    {
+        self.frames.get(step)
+    }
+
+    pub fn render(&self, step: usize) -> Option<String>
+    {
+        let frame = self.frame(step)?;
+
+        let header = match frame.operation
+        {
+            Some(operation) => format!("== move {} of {}: {} ({}) ==", frame.step, self.moves(), operation, self.crane),
+            None => format!("== start ({} moves, {}) ==", self.moves(), self.crane),
+        };
+
+        let mut lines = vec![header];
+        lines.extend(render_highlighted(&frame.stacks, &frame.moved));
+
+        Some(lines.join("\n"))
+    }
+
+    pub fn export(&self, path: &str) -> io::Result<()>
+    {
+        let frames: Vec<String> = (0..self.frames.len()).filter_map(|step| self.render(step)).collect();
+        fs::write(path, frames.join("\n\n") + "\n")
+    }
+
+    // n (or just enter) steps forward, p steps back, j <k> jumps to move k, q quits.
+    pub fn interactive<R: BufRead, W: Write>(&self, input: R, output: &mut W) -> io::Result<()>
+    {
+        let mut step = 0;
+        writeln!(output, "{}", self.render(step).unwrap())?;
+
+        for line in input.lines()
+        {
+            let line = line?;
+            let mut tokens = line.split_whitespace();
+
+            match tokens.next()
+            {
+                None | Some("n") => step = usize::min(step + 1, self.moves()),
+                Some("p") => step = step.saturating_sub(1),
+                Some("j") =>
+                {
+                    match tokens.next().and_then(|k| k.parse::<usize>().ok())
+                    {
+                        Some(k) if k <= self.moves() => step = k,
+                        _ =>
+                        {
+                            writeln!(output, "Moves run from 0 to {}.", self.moves())?;
+                            continue;
+                        },
+                    }
+                },
+                Some("q") => break,
+                Some(other) =>
+                {
+                    writeln!(output, "Unknown command {}.  Use n, p, j <move> or q.", other)?;
+                    continue;
+                },
+            }
+
+            writeln!(output, "{}", self.render(step).unwrap())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use std::io::Cursor;
+
+    use crate::day5::crane::{CrateMover9000, CrateMover9001};
+    use crate::day5::validate::validate_program;
+
+    use super::Replay;
+
+    fn sample<'a>() -> Vec<&'a str>
+    {
+        vec!["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 ", "", "move 1 from 2 to 1", "move 3 from 1 to 3", "move 2 from 2 to 1", "move 1 from 1 to 2"]
+    }
+
+    #[test]
+    pub fn a_replay_has_a_frame_for_the_start_and_every_move()
+    {
+        let replay = Replay::record(sample(), &CrateMover9000).unwrap();
+
+        assert_eq!(replay.moves(), 4);
+        assert_eq!(replay.frame(0).unwrap().stacks, vec![vec!["Z", "N"], vec!["M", "C", "D"], vec!["P"]]);
+        assert_eq!(replay.frame(4).unwrap().stacks, vec![vec!["C"], vec!["M"], vec!["P", "D", "N", "Z"]]);
+        assert!(replay.frame(5).is_none());
+    }
+
+    #[test]
+    pub fn the_crates_that_moved_are_highlighted()
+    {
+        let replay = Replay::record(sample(), &CrateMover9001).unwrap();
+
+        assert_eq!(replay.frame(2).unwrap().moved, vec![(2, 1), (2, 2), (2, 3)]);
+        assert_eq!(replay.render(1).unwrap(), "== move 1 of 4: move 1 from 2 to 1 (CrateMover 9001) ==\n{D}        \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 ");
+    }
+
+    #[test]
+    pub fn a_broken_program_fails_with_the_first_diagnostic_validation_gives()
+    {
+        let mut lines = sample();
+        lines.insert(6, "move 5 from 3 to 1");
+        lines.push("move 1 from 4 to 1");
+
+        let failure = Replay::record(lines.clone(), &CrateMover9000).err().unwrap();
+
+        assert_eq!(failure, validate_program(lines).remove(0));
+    }
+
+    #[test]
+    pub fn interactive_mode_steps_and_jumps_between_frames()
+    {
+        let replay = Replay::record(sample(), &CrateMover9000).unwrap();
+        let mut output = Vec::new();
+
+        replay.interactive(Cursor::new("n\nj 4\nn\np\nj 9\nq\nn\n"), &mut output).unwrap();
+
+        let shown = String::from_utf8(output).unwrap();
+        let headers: Vec<&str> = shown.lines().filter(|line| line.starts_with("==")).collect();
+        assert_eq!(headers.len(), 5);
+        assert!(headers[2].starts_with("== move 4 of 4"));
+        assert!(headers[4].starts_with("== move 3 of 4"));
+        assert!(shown.contains("Moves run from 0 to 4."));
+    }
+}
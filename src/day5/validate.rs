@@ -0,0 +1,228 @@
+use log::debug;
+
+use super::advent::{build_stacks, get_final_state, input_processor};
+use super::crane::Crane;
+
+#[derive(Debug, PartialEq)]
+pub enum MoveFault
+{
+    Malformed,
+    // Stack numbers as written in the program, i.e. 1 based.
+    NoSuchStack(usize),
+    NotEnoughCrates { stack: usize, requested: u64, available: usize },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct MoveDiagnostic
+{
+    // 1 based line number in the whole input, drawing included.
+    pub line: usize,
+    pub operation: String,
+    pub fault: MoveFault,
+    // Height of every stack just before the bad move.
+    pub heights: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Leniency
+{
+    // Leave a bad move out entirely.
+    Skip,
+    // Move as many crates as the stack actually has.  Moves naming a stack that doesn't exist are still skipped.
+    Clamp,
+}
+
+// Reads "move N from A to B" without trusting any of it.  Stack numbers are returned exactly as written.
+pub fn parse_operation(operation: &str) -> Result<(u64, usize, usize), MoveFault>
+{
+    let tokens: Vec<&str> = operation.split_whitespace().collect();
+
+    match tokens.as_slice()
+    {
+        ["move", count, "from", from, "to", to] =>
+        {
+            match (count.parse::<u64>(), from.parse::<usize>(), to.parse::<usize>())
+            {
+                (Ok(count), Ok(from), Ok(to)) => Ok((count, from, to)),
+                _ => Err(MoveFault::Malformed),
+            }
+        },
+        _ => Err(MoveFault::Malformed),
+    }
+}
+
+// Checks a move against the current stack heights, handing back 0 based stack indices when it is sound.
+pub fn check_move(heights: &[usize], count: u64, from: usize, to: usize) -> Result<(usize, usize), MoveFault>
+{
+    for stack in [from, to]
+    {
+        if stack == 0 || stack > heights.len()
+        {
+            return Err(MoveFault::NoSuchStack(stack));
+        }
+    }
+
+    if count > heights[from - 1] as u64
+    {
+        return Err(MoveFault::NotEnoughCrates { stack: from, requested: count, available: heights[from - 1] });
+    }
+
+    Ok((from - 1, to - 1))
+}
+
+// Walks the program over stack heights alone, without moving a single crate.  A bad move is reported and then left
+// out, so the heights reported for later moves are what they would be if the bad ones were skipped.
+pub fn validate_program(lines: Vec<&str>) -> Vec<MoveDiagnostic>
+{
+    let (setup, operations) = input_processor(lines);
+    // Operations start after the drawing and the blank line that follows it.
+    let first_line = setup.len() + 2;
+
+    let mut heights: Vec<usize> = build_stacks(setup).borrow().iter().map(|stack| stack.len()).collect();
+    let mut diagnostics = Vec::new();
+
+    for (index, operation) in operations.iter().enumerate()
+    {
+        let checked = parse_operation(operation).and_then(|(count, from, to)| check_move(&heights, count, from, to).map(|indices| (count, indices)));
+
+        match checked
+        {
+            Ok((count, (from, to))) =>
+            {
+                heights[from] -= count as usize;
+                heights[to] += count as usize;
+            },
+            Err(fault) =>
+            {
+                diagnostics.push(MoveDiagnostic { line: first_line + index, operation: operation.to_string(), fault, heights: heights.clone() });
+            },
+        }
+    }
+
+    diagnostics
+}
+
+// Runs the program with the given crane, reporting the moves that had to be skipped or clamped along the way.
+pub fn execute_lenient(lines: Vec<&str>, crane: &dyn Crane, leniency: Leniency) -> (String, Vec<MoveDiagnostic>)
+{
+    let (setup, operations) = input_processor(lines);
+    let first_line = setup.len() + 2;
+
+    let cargo_stacks = build_stacks(setup);
+    let mut diagnostics = Vec::new();
+
+    for (index, operation) in operations.iter().enumerate()
+    {
+        let heights: Vec<usize> = cargo_stacks.borrow().iter().map(|stack| stack.len()).collect();
+
+        let fault = match parse_operation(operation)
+        {
+            Ok((count, from, to)) => match check_move(&heights, count, from, to)
+            {
+                Ok((from, to)) =>
+                {
+                    crane.move_crates(&cargo_stacks, count, from, to);
+                    continue;
+                },
+                Err(MoveFault::NotEnoughCrates { stack, requested, available }) =>
+                {
+                    if leniency == Leniency::Clamp
+                    {
+                        debug!("Clamping {} down to {} crates", operation, available);
+                        crane.move_crates(&cargo_stacks, available as u64, from - 1, to - 1);
+                    }
+                    MoveFault::NotEnoughCrates { stack, requested, available }
+                },
+                Err(fault) => fault,
+            },
+            Err(fault) => fault,
+        };
+
+        diagnostics.push(MoveDiagnostic { line: first_line + index, operation: operation.to_string(), fault, heights });
+    }
+
+    (get_final_state(&cargo_stacks), diagnostics)
+}
+
+pub fn describe(diagnostic: &MoveDiagnostic) -> String
+{
+    let problem = match &diagnostic.fault
+    {
+        MoveFault::Malformed => String::from("is not of the form 'move N from A to B'"),
+        MoveFault::NoSuchStack(stack) => format!("refers to stack {}, but stacks are numbered 1 to {}", stack, diagnostic.heights.len()),
+        MoveFault::NotEnoughCrates { stack, requested, available } => format!("moves {} crates but stack {} only holds {}", requested, stack, available),
+    };
+
+    format!("line {}: '{}' {} (stack heights {:?})", diagnostic.line, diagnostic.operation, problem, diagnostic.heights)
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day5::crane::{CrateMover9000, CrateMover9001};
+
+    use super::{execute_lenient, parse_operation, validate_program, Leniency, MoveFault};
+
+    fn program<'a>(moves: &[&'a str]) -> Vec<&'a str>
+    {
+        let mut lines = vec!["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 ", ""];
+        lines.extend_from_slice(moves);
+        lines
+    }
+
+    #[test]
+    pub fn parse_operation_rejects_anything_that_is_not_a_move()
+    {
+        assert_eq!(parse_operation("move 1 from 2 to 1"), Ok((1, 2, 1)));
+        assert_eq!(parse_operation("move 1 to 2 from 3"), Err(MoveFault::Malformed));
+        assert_eq!(parse_operation("move x from 2 to 1"), Err(MoveFault::Malformed));
+        assert_eq!(parse_operation("move 1 from -2 to 1"), Err(MoveFault::Malformed));
+    }
+
+    #[test]
+    pub fn a_sound_program_has_no_diagnostics()
+    {
+        let lines = program(&["move 1 from 2 to 1", "move 3 from 1 to 3", "move 2 from 2 to 1", "move 1 from 1 to 2"]);
+
+        assert!(validate_program(lines).is_empty());
+    }
+
+    #[test]
+    pub fn every_bad_move_is_reported_with_its_line_and_the_stack_heights()
+    {
+        let lines = program(&["move 1 from 0 to 1", "move 4 from 1 to 2", "move 1 from 2 to 4", "shuffle", "move 1 from 3 to 1"]);
+
+        let diagnostics = validate_program(lines);
+
+        assert_eq!(diagnostics.len(), 4);
+        assert_eq!(diagnostics[0].line, 6);
+        assert_eq!(diagnostics[0].fault, MoveFault::NoSuchStack(0));
+        assert_eq!(diagnostics[1].fault, MoveFault::NotEnoughCrates { stack: 1, requested: 4, available: 2 });
+        assert_eq!(diagnostics[1].heights, vec![2, 3, 1]);
+        assert_eq!(diagnostics[2].fault, MoveFault::NoSuchStack(4));
+        assert_eq!(diagnostics[3].line, 9);
+        assert_eq!(diagnostics[3].fault, MoveFault::Malformed);
+    }
+
+    #[test]
+    pub fn lenient_skip_leaves_bad_moves_out()
+    {
+        let lines = program(&["move 5 from 2 to 1", "move 1 from 3 to 1"]);
+
+        let (tops, diagnostics) = execute_lenient(lines, &CrateMover9000, Leniency::Skip);
+
+        assert_eq!(tops, "PD ");
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    pub fn lenient_clamp_moves_whatever_is_there()
+    {
+        let lines = program(&["move 5 from 2 to 1", "move 1 from 9 to 1"]);
+
+        let (tops, diagnostics) = execute_lenient(lines, &CrateMover9001, Leniency::Clamp);
+
+        assert_eq!(tops, "D P");
+        assert_eq!(diagnostics.len(), 2);
+    }
+}
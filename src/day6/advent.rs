@@ -1,86 +1,26 @@
-use std::str::Chars;
+use super::search::{find_marker_with, Search};
 
-use log::debug;
+pub const PACKET_WINDOW: usize = 4;
+pub const MESSAGE_WINDOW: usize = 14;
 
-use crate::lib::lib::char_to_num;
-
-
-
-pub fn scan_datastream(stream: &str) -> usize
+// The start-of-packet and start-of-message markers, in that order.
+pub fn find_markers(stream: &[u8]) -> (Option<usize>, Option<usize>)
 {
-    let mut dupes = [0u8; 26];
-    let mut char_buf = stream.chars();
-    let mut has_duplicate = false;
-
-    let mut set_indices = prefill_buf(14, &mut dupes, &mut char_buf);
-    let mut end_of_window = 14;
-    let mut dupe_count: usize = 0;
-
-    for i in 0..26
-    {
-        if dupes[i] > 1
-        {
-            has_duplicate = true;
-            dupe_count += dupes[i] as usize - 1;
-        }
-    }
-
-    debug!("Start condition");
-    debug!("---------------");
-    debug!("List of indices set during initialization: {:?}", set_indices);
-    debug!("current end of window: {}", end_of_window);
-    debug!("Count of duplicates: {}", dupe_count);
-
-    while has_duplicate && !dupes.is_empty()
-    {
-        let first_in = set_indices.remove(0);
-        debug!("Dequeue'd first in index: {}", first_in);
-        dupes[first_in] -= 1;
-        if dupes[first_in] > 0
-        {
-            dupe_count -= 1;
-        }
-        if let Some(next_char) = char_buf.next()
-        {
-            debug!("Testing character <{}>", next_char);
-            let char_num = char_to_num(&next_char);
-            end_of_window += 1;
-            debug!("End of window: {}", end_of_window);
-            dupes[char_num] += 1;
-            if dupes[char_num] > 1
-            {
-                dupe_count += 1;
-            }
-            set_indices.push(char_num);
-        }
-
-        debug!("Dupe count: {}", dupe_count);
-
-        if dupe_count == 0 { has_duplicate = false;}
-    }
-
-    return end_of_window;
+    (find_marker(stream, PACKET_WINDOW), find_marker(stream, MESSAGE_WINDOW))
 }
 
-fn prefill_buf(fill_count: usize, dupe_buf: &mut [u8], char_buf: &mut Chars) -> Vec<usize>
+// Position just past the first run of window bytes that are all different, or None if the stream never has one.
+// Works over any bytes at all, not just lowercase letters.
+pub fn find_marker(stream: &[u8], window: usize) -> Option<usize>
 {
-    let mut set_indices = Vec::<usize>::new();
-    for _i in 0..fill_count
-    {
-        if let Some(next_char) = char_buf.next()
-        {
-            debug!("Testing character <{}>", next_char);
-            let char_num = char_to_num(&next_char);
-            set_indices.push(char_num);
-            dupe_buf[char_num] += 1;
-        }
-        else
-        {
-            return set_indices;
-        }
-    }
+    find_marker_with(stream, window, Search::LastSeen)
+}
 
-    return set_indices;
+// A stream without a message marker reads as one that ends in it, as it always has, and one too short to hold a
+// whole window still reads as the end of the first window.
+pub fn scan_datastream(stream: &str) -> usize
+{
+    find_marker_with(stream.as_bytes(), MESSAGE_WINDOW, Search::Queue).unwrap_or(usize::max(stream.len(), MESSAGE_WINDOW))
 }
 
 #[cfg(test)]
@@ -120,4 +60,36 @@ pub fn zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw()
 {
     let test_line = "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw";
     assert_eq!(scan_datastream(test_line), 26);
+}
+
+#[test]
+pub fn a_stream_without_a_message_marker_scans_to_its_end()
+{
+    assert_eq!(scan_datastream("abcabcabcabcabcabcabc"), 21);
+}
+
+#[test]
+pub fn a_stream_shorter_than_a_message_window_scans_to_the_end_of_the_window()
+{
+    assert_eq!(scan_datastream("abcdef"), 14);
+    assert_eq!(scan_datastream("aab"), 14);
+    assert_eq!(scan_datastream(""), 14);
+}
+
+#[test]
+pub fn find_markers_gives_the_packet_and_message_markers()
+{
+    assert_eq!(find_markers(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb"), (Some(7), Some(19)));
+    assert_eq!(find_markers(b"bvwbjplbgvbhsrlpgdmjqwftvncz"), (Some(5), Some(23)));
+    assert_eq!(find_markers(b"nppdvjthqldpwncqszvftbrmjlhg"), (Some(6), Some(23)));
+    assert_eq!(find_markers(b"nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), (Some(10), Some(29)));
+    assert_eq!(find_markers(b"zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), (Some(11), Some(26)));
+}
+
+#[test]
+pub fn find_marker_handles_any_byte_and_streams_without_a_marker()
+{
+    assert_eq!(find_marker(b"AAB\x00\xffA", 4), Some(5));
+    assert_eq!(find_marker(b"aaaaaaa", 2), None);
+    assert_eq!(find_marker(b"abc", 4), None);
 }
\ No newline at end of file
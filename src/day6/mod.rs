@@ -1 +1,3 @@
-pub mod advent;
\ No newline at end of file
+pub mod advent;
+pub mod stream;
+pub mod search;
\ No newline at end of file
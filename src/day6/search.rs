@@ -0,0 +1,315 @@
+use std::slice::Iter;
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+// The ways we know of to find the first run of window distinct bytes.  All of them give the same answer.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Search
+{
+    // The original scan_datastream approach: a Vec of the window's bytes, dequeued from the front with remove(0).
+    Queue,
+    // Per-byte counts over a window slid one byte at a time.
+    Counter,
+    // Where every byte value was last seen, letting the window jump past the earlier copy of a duplicate rather than
+    // sliding by one.
+    LastSeen,
+    // A rolling XOR of one bit per byte over the window, which has exactly window bits set only when the window is
+    // distinct, skipping ahead past duplicates rather than sliding by one.
+    Bitmask,
+}
+
+pub const ALL_SEARCHES: [Search; 4] = [Search::Queue, Search::Counter, Search::LastSeen, Search::Bitmask];
+
+pub fn find_marker_with(stream: &[u8], window: usize, search: Search) -> Option<usize>
+{
+    match search
+    {
+        Search::Queue => queue_search(stream, window),
+        Search::Counter => counter_search(stream, window),
+        Search::LastSeen => last_seen_search(stream, window),
+        Search::Bitmask => bitmask_search(stream, window),
+    }
+}
+
+fn queue_search(stream: &[u8], window: usize) -> Option<usize>
+{
+    let mut dupes = [0u32; 256];
+    let mut byte_buf = stream.iter();
+
+    let mut set_indices = prefill_buf(window, &mut dupes, &mut byte_buf);
+    if set_indices.len() < window
+    {
+        return None;
+    }
+
+    let mut end_of_window = window;
+    let mut dupe_count: usize = dupes.iter().filter(|count| **count > 1).map(|count| *count as usize - 1).sum();
+
+    while dupe_count > 0
+    {
+        let next_byte = *byte_buf.next()? as usize;
+
+        let first_in = set_indices.remove(0);
+        dupes[first_in] -= 1;
+        if dupes[first_in] > 0
+        {
+            dupe_count -= 1;
+        }
+
+        end_of_window += 1;
+        dupes[next_byte] += 1;
+        if dupes[next_byte] > 1
+        {
+            dupe_count += 1;
+        }
+        set_indices.push(next_byte);
+    }
+
+    Some(end_of_window)
+}
+
+fn prefill_buf(fill_count: usize, dupe_buf: &mut [u32], byte_buf: &mut Iter<u8>) -> Vec<usize>
+{
+    let mut set_indices = Vec::<usize>::with_capacity(fill_count);
+    for byte in byte_buf.take(fill_count)
+    {
+        set_indices.push(*byte as usize);
+        dupe_buf[*byte as usize] += 1;
+    }
+
+    set_indices
+}
+
+fn counter_search(stream: &[u8], window: usize) -> Option<usize>
+{
+    if window == 0
+    {
+        return Some(0);
+    }
+
+    let mut counts = [0u32; 256];
+    // Number of bytes in the window that share their value with an earlier byte in the window.
+    let mut dupe_count: usize = 0;
+
+    for (index, byte) in stream.iter().enumerate()
+    {
+        counts[*byte as usize] += 1;
+        if counts[*byte as usize] > 1
+        {
+            dupe_count += 1;
+        }
+
+        if index >= window
+        {
+            let leaving = stream[index - window] as usize;
+            counts[leaving] -= 1;
+            if counts[leaving] > 0
+            {
+                dupe_count -= 1;
+            }
+        }
+
+        if index + 1 >= window && dupe_count == 0
+        {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+// Grows a window of distinct bytes one byte at a time, remembering where every byte value was last seen.  When the
+// next byte is already in the window, no window starting at or before its earlier copy can be distinct, so the start
+// jumps straight to just past that copy.  Each byte costs one lookup and one max, with no branch on the data, and the
+// window stays distinct throughout, so its length is the distinct count and no counting is needed.
+fn last_seen_search(stream: &[u8], window: usize) -> Option<usize>
+{
+    if window == 0
+    {
+        return Some(0);
+    }
+
+    // One past where each byte value was last seen, 0 for never.
+    let mut last_seen = [0usize; 256];
+    let mut start = 0;
+
+    for (index, byte) in stream.iter().enumerate()
+    {
+        start = usize::max(start, last_seen[*byte as usize]);
+        last_seen[*byte as usize] = index + 1;
+
+        // The window only ever grows a byte at a time, so it cannot step past window without landing on it.
+        if index + 1 - start == window
+        {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+// Flips byte's bit in the mask and keeps count of how many bits are set.
+fn toggle(mask: &mut [u64; 4], set_bits: &mut usize, byte: u8)
+{
+    let bit = 1u64 << (byte % 64);
+    let word = &mut mask[byte as usize / 64];
+
+    if *word & bit == 0
+    {
+        *set_bits += 1;
+    }
+    else
+    {
+        *set_bits -= 1;
+    }
+    *word ^= bit;
+}
+
+// XORs every byte's bit into the mask as it joins the window and back out as it leaves.  A pair of equal bytes cancels
+// out, so the window is distinct exactly when window bits are set.  When it is not, every window starting at or before
+// the earlier copy of the latest duplicate seen so far holds that duplicate, so the start jumps straight past it,
+// found in one lookup.  Every byte goes into the mask and out of it at most once.
+fn bitmask_search(stream: &[u8], window: usize) -> Option<usize>
+{
+    if window == 0
+    {
+        return Some(0);
+    }
+    if window > 256
+    {
+        return None;
+    }
+
+    let mut mask = [0u64; 4];
+    let mut set_bits = 0;
+    // One past where each byte value was last seen, 0 for never.
+    let mut last_seen = [0usize; 256];
+    // No window starting before this can be distinct.
+    let mut earliest = 0;
+    let mut start = 0;
+    let mut end = 0;
+
+    loop
+    {
+        while end < start + window
+        {
+            let byte = *stream.get(end)?;
+            toggle(&mut mask, &mut set_bits, byte);
+            earliest = usize::max(earliest, last_seen[byte as usize]);
+            last_seen[byte as usize] = end + 1;
+            end += 1;
+        }
+
+        if set_bits == window
+        {
+            return Some(end);
+        }
+
+        // A window that is not distinct holds a duplicate pair, so earliest is already past start.
+        for byte in &stream[start..earliest]
+        {
+            toggle(&mut mask, &mut set_bits, *byte);
+        }
+        start = earliest;
+    }
+}
+
+// len bytes drawn from only window - 1 letters, so there can be no marker among them, followed by window bytes that
+// are all distinct.  The marker therefore ends somewhere after len and no later than len + window.
+pub fn benchmark_stream(len: usize, window: usize, seed: u64) -> Vec<u8>
+{
+    if !(2..=128).contains(&window)
+    {
+        panic!("Benchmark windows run from 2 to 128, not {}.", window);
+    }
+
+    let mut state = seed;
+    let mut stream: Vec<u8> = (0..len).map(|_|
+    {
+        // Knuth's MMIX LCG; the top bits are the random ones.
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 33) % (window as u64 - 1)) as u8
+    }).collect();
+    stream.extend((0..window).map(|offset| 128 + offset as u8));
+
+    stream
+}
+
+// Times every search over the same stream, best of rounds.
+pub fn benchmark(stream: &[u8], window: usize, rounds: usize) -> Vec<(Search, Duration, Option<usize>)>
+{
+    ALL_SEARCHES.iter().map(|search|
+    {
+        let mut best = Duration::MAX;
+        let mut found = None;
+        for _round in 0..rounds
+        {
+            let started = Instant::now();
+            found = find_marker_with(stream, window, *search);
+            best = best.min(started.elapsed());
+        }
+
+        debug!("{:?} found {:?} in {:?}", search, found, best);
+        (*search, best, found)
+    }).collect()
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use super::{benchmark, benchmark_stream, find_marker_with, ALL_SEARCHES};
+
+    fn cross_check(stream: &[u8], window: usize) -> Option<usize>
+    {
+        let answers: Vec<Option<usize>> = ALL_SEARCHES.iter().map(|search| find_marker_with(stream, window, *search)).collect();
+
+        assert!(answers.iter().all(|answer| *answer == answers[0]), "{:?} disagree on window {} of {:?}", answers, window, stream);
+        answers[0]
+    }
+
+    #[test]
+    pub fn every_search_agrees_on_the_puzzle_examples()
+    {
+        assert_eq!(cross_check(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4), Some(7));
+        assert_eq!(cross_check(b"mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), Some(19));
+        assert_eq!(cross_check(b"zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14), Some(26));
+    }
+
+    #[test]
+    pub fn every_search_agrees_on_pseudo_random_streams_and_edge_cases()
+    {
+        let mut state: u64 = 7;
+        for length in 0..200
+        {
+            let stream: Vec<u8> = (0..length).map(|_|
+            {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                (state >> 59) as u8
+            }).collect();
+
+            for window in 0..20
+            {
+                cross_check(&stream, window);
+            }
+        }
+
+        let every_byte: Vec<u8> = (0..=255).collect();
+        assert_eq!(cross_check(&every_byte, 256), Some(256));
+        assert_eq!(cross_check(&every_byte, 257), None);
+    }
+
+    #[test]
+    pub fn the_benchmark_stream_hides_its_marker_at_the_end()
+    {
+        let stream = benchmark_stream(5000, 14, 1);
+        let results = benchmark(&stream, 14, 1);
+
+        let found = results[0].2.unwrap();
+        assert!(found > 5000 && found <= 5014);
+        for (search, _, answer) in results
+        {
+            assert_eq!(answer, Some(found), "{:?}", search);
+        }
+    }
+}
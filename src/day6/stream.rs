@@ -0,0 +1,278 @@
+use std::io::{self, ErrorKind, Read};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+// Scans any reader for distinct-window markers without ever holding more than one chunk and one window of the
+// stream.  Yields the position just past every window of distinct bytes, so for a stream with no repeats at all that
+// is every position from window onwards.
+pub struct MarkerScanner<R: Read>
+{
+    reader: R,
+    window: usize,
+    chunk: Vec<u8>,
+    chunk_len: usize,
+    chunk_pos: usize,
+    // The last window bytes seen, oldest at head once the ring is full.
+    ring: Vec<u8>,
+    head: usize,
+    filled: usize,
+    counts: [u32; 256],
+    dupe_count: usize,
+    position: usize,
+}
+
+impl<R: Read> MarkerScanner<R>
+{
+    pub fn new(reader: R, window: usize) -> MarkerScanner<R>
+    {
+        if window == 0
+        {
+            panic!("A marker window needs at least one byte in it.");
+        }
+
+        MarkerScanner
+        {
+            reader,
+            window,
+            chunk: vec![0; CHUNK_SIZE],
+            chunk_len: 0,
+            chunk_pos: 0,
+            ring: vec![0; window],
+            head: 0,
+            filled: 0,
+            counts: [0; 256],
+            dupe_count: 0,
+            position: 0,
+        }
+    }
+
+    pub fn window(&self) -> usize
+    {
+        self.window
+    }
+
+    // Number of bytes consumed from the reader so far.
+    pub fn position(&self) -> usize
+    {
+        self.position
+    }
+
+    // Forgets the current window, so the next marker has to be made entirely of bytes read after this call.
+    pub fn reset(&mut self)
+    {
+        self.counts = [0; 256];
+        self.dupe_count = 0;
+        self.filled = 0;
+        self.head = 0;
+    }
+
+    pub fn frames(self) -> Frames<R>
+    {
+        Frames { scanner: self, current: None, finished: false }
+    }
+
+    fn next_byte(&mut self) -> Option<io::Result<u8>>
+    {
+        while self.chunk_pos == self.chunk_len
+        {
+            match self.reader.read(&mut self.chunk)
+            {
+                Ok(0) => return None,
+                Ok(read) =>
+                {
+                    self.chunk_len = read;
+                    self.chunk_pos = 0;
+                },
+                Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Some(Err(err)),
+            }
+        }
+
+        let byte = self.chunk[self.chunk_pos];
+        self.chunk_pos += 1;
+        Some(Ok(byte))
+    }
+
+    fn push(&mut self, byte: u8)
+    {
+        if self.filled == self.window
+        {
+            let leaving = self.ring[self.head] as usize;
+            self.counts[leaving] -= 1;
+            if self.counts[leaving] > 0
+            {
+                self.dupe_count -= 1;
+            }
+        }
+        else
+        {
+            self.filled += 1;
+        }
+
+        self.ring[self.head] = byte;
+        self.head = (self.head + 1) % self.window;
+
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] > 1
+        {
+            self.dupe_count += 1;
+        }
+    }
+}
+
+impl<R: Read> Iterator for MarkerScanner<R>
+{
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<io::Result<usize>>
+    {
+        loop
+        {
+            let byte = match self.next_byte()?
+            {
+                Ok(byte) => byte,
+                Err(err) => return Some(Err(err)),
+            };
+
+            self.position += 1;
+            self.push(byte);
+
+            if self.filled == self.window && self.dupe_count == 0
+            {
+                return Some(Ok(self.position));
+            }
+        }
+    }
+}
+
+// A packet is everything between the end of one start marker and the beginning of the next, or the end of the stream.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Packet
+{
+    // Where the start marker itself begins.
+    pub marker: usize,
+    // First byte after the marker.
+    pub start: usize,
+    // One past the last byte of the packet.
+    pub end: usize,
+}
+
+// Splits a stream into packets.  After every marker the scanner starts over, so successive markers never overlap and
+// the bytes of one packet are never counted towards the next marker.  Anything before the first marker is dropped.
+pub struct Frames<R: Read>
+{
+    scanner: MarkerScanner<R>,
+    current: Option<(usize, usize)>,
+    finished: bool,
+}
+
+impl<R: Read> Iterator for Frames<R>
+{
+    type Item = io::Result<Packet>;
+
+    fn next(&mut self) -> Option<io::Result<Packet>>
+    {
+        if self.finished
+        {
+            return None;
+        }
+
+        loop
+        {
+            match self.scanner.next()
+            {
+                Some(Ok(end_of_marker)) =>
+                {
+                    self.scanner.reset();
+                    let marker = end_of_marker - self.scanner.window();
+                    let finished_packet = self.current.map(|(previous, start)| Packet { marker: previous, start, end: marker });
+                    self.current = Some((marker, end_of_marker));
+
+                    if finished_packet.is_some()
+                    {
+                        return finished_packet.map(Ok);
+                    }
+                },
+                Some(Err(err)) => return Some(Err(err)),
+                None =>
+                {
+                    self.finished = true;
+                    return self.current.map(|(marker, start)| Ok(Packet { marker, start, end: self.scanner.position() }));
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use std::io::{self, Read};
+
+    use crate::day6::advent::find_marker;
+
+    use super::{MarkerScanner, Packet};
+
+    // Hands the stream out a single byte per read, to push every chunk boundary case through the scanner.
+    struct Trickle<'a>
+    {
+        bytes: &'a [u8],
+    }
+
+    impl<'a> Read for Trickle<'a>
+    {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+        {
+            match self.bytes.split_first()
+            {
+                Some((byte, rest)) if !buf.is_empty() =>
+                {
+                    buf[0] = *byte;
+                    self.bytes = rest;
+                    Ok(1)
+                },
+                _ => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    pub fn the_first_marker_matches_find_marker()
+    {
+        for stream in ["mjqjpqmgbljsphdztnvjfqwrcgsmlb", "bvwbjplbgvbhsrlpgdmjqwftvncz", "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"]
+        {
+            for window in [4, 14]
+            {
+                let first = MarkerScanner::new(Trickle { bytes: stream.as_bytes() }, window).next().map(|result| result.unwrap());
+
+                assert_eq!(first, find_marker(stream.as_bytes(), window), "{} with window {}", stream, window);
+            }
+        }
+    }
+
+    #[test]
+    pub fn every_marker_position_is_reported()
+    {
+        let markers: Vec<usize> = MarkerScanner::new("aabcbdd".as_bytes(), 3).map(|result| result.unwrap()).collect();
+
+        assert_eq!(markers, vec![4, 6]);
+    }
+
+    #[test]
+    pub fn framing_splits_the_stream_between_successive_markers()
+    {
+        let packets: Vec<Packet> = MarkerScanner::new(Trickle { bytes: b"aaabcdhihijkllmnoZ" }, 4).frames().map(|result| result.unwrap()).collect();
+
+        assert_eq!(packets, vec![
+            Packet { marker: 2, start: 6, end: 8 },
+            Packet { marker: 8, start: 12, end: 13 },
+            Packet { marker: 13, start: 17, end: 18 },
+        ]);
+    }
+
+    #[test]
+    pub fn a_stream_without_markers_has_no_packets()
+    {
+        assert_eq!(MarkerScanner::new("abab".as_bytes(), 3).frames().count(), 0);
+    }
+}
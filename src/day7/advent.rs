@@ -1,7 +1,9 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{collections::{HashSet, VecDeque}, fmt};
 
 use log::{debug, error};
 
+use super::cleanup::DiskSpec;
+
 
 pub fn dir_solver(inputs: Vec<&str>)
 {
@@ -24,13 +26,14 @@ pub fn space_finder(inputs: Vec<&str>)
 {
     let mut fs = fill_fs(inputs);
 
-    fs.cwd("/");
-    let used = fs.get_cwd().as_ref().borrow().size();
-    let free = 70000000 - used;
+    fs.cwd("/").unwrap();
+    let spec = DiskSpec::default();
+    let used = fs.get_cwd().size();
+    let free = spec.total - used;
     
-    if free < 30000000
+    if free < spec.needed
     {
-        let needed = 30000000 - free;
+        let needed = spec.needed - free;
         debug!("Space free {}, needed {}", free, needed);
         let results = fs.breadth_first_filter(|c| c >= needed);
 
@@ -47,35 +50,89 @@ pub fn space_finder(inputs: Vec<&str>)
 }
 
 fn fill_fs(inputs: Vec<&str> ) -> Filesystem
+{
+    let (fs, warnings) = fill_fs_checked(inputs);
+    for warning in warnings
+    {
+        println!("Warning: {}", warning);
+    }
+
+    fs
+}
+
+// Replays a transcript, treating every ls as an authoritative snapshot of its directory.  Anything inconsistent about
+// the transcript comes back as a warning alongside the filesystem.
+pub fn fill_fs_checked(inputs: Vec<&str>) -> (Filesystem, Vec<FsWarning>)
 {
     let mut fs = Filesystem::new();
+    let mut warnings = Vec::new();
+    // Whatever a cd that went nowhere left the transcript looking at, it is not a directory we know of, so nothing
+    // listed there can be placed until a cd succeeds again.
+    let mut cwd_known = true;
+
     for input in inputs
     {
         match Shell::line_processor(input)
         {
             LineType::ChangeDirectory(target) => 
             {
-                fs.cwd(&target)
+                warnings.extend(fs.end_listing());
+                let changed = rejected(input, fs.cwd(&target).map_err(ShellError::Path));
+                cwd_known = changed.is_none();
+                warnings.extend(changed);
+            },
+            LineType::List => 
+            {
+                warnings.extend(fs.end_listing());
+                if cwd_known
+                {
+                    fs.begin_listing();
+                }
+            },
+            LineType::DirectoryEntry(_) | LineType::FileEntry(_) if !cwd_known => 
+            {
+                warnings.push(FsWarning::Dropped { entry: String::from(input.trim()) });
             },
-            LineType::List => {},
             LineType::DirectoryEntry(dir_name) => 
             {
-                let new_dir = Directory::new(dir_name);
-                fs.create_dir(new_dir);
+                warnings.extend(fs.list_dir(dir_name));
             },
             LineType::FileEntry((name, size)) => 
             {
-                let file = File {name, size};
-                let dir = fs.get_cwd();
-                dir.as_ref().borrow_mut().add_file(file);
-                fs.update_sizes();
+                warnings.extend(fs.list_file(File {name, size}));
+            },
+            LineType::MakeDirectory(path) => 
+            {
+                warnings.extend(fs.end_listing());
+                warnings.extend(rejected(input, fs.mkdir(&path)));
+            },
+            LineType::Remove((path, recursive)) => 
+            {
+                warnings.extend(fs.end_listing());
+                warnings.extend(rejected(input, fs.rm(&path, recursive)));
+            },
+            LineType::Move((from, to)) => 
+            {
+                warnings.extend(fs.end_listing());
+                warnings.extend(rejected(input, fs.mv(&from, &to)));
+            },
+            LineType::Touch((path, size)) => 
+            {
+                warnings.extend(fs.end_listing());
+                warnings.extend(rejected(input, fs.touch(&path, size)));
             },
             LineType::Noop => {debug!("An empty line has slipped through the inputs.")},
             LineType::UnknownToken => {error!("A malformed token {} has slipped through the inputs.", input)},
         }
     }
-    
-    return fs;
+    warnings.extend(fs.end_listing());
+
+    (fs, warnings)
+}
+
+fn rejected(command: &str, result: Result<(), ShellError>) -> Option<FsWarning>
+{
+    result.err().map(|reason| FsWarning::Rejected { command: String::from(command.trim()), reason })
 }
 
 pub struct File
@@ -84,21 +141,30 @@ pub struct File
     pub size: usize,
 }
 
+// Handle for a directory in a Filesystem.  Only the filesystem that handed it out can make sense of it.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub struct DirId(usize);
+
+// The root directory is always the first one in the arena.
+pub const ROOT: DirId = DirId(0);
 
 pub struct Directory
 {
     name: String,
-    children: Vec<usize>,
-    parent: Option<usize>,
+    children: Vec<DirId>,
+    parent: Option<DirId>,
     files: Vec<File>,
-    size: usize
+    // Files here plus everything in every subdirectory.
+    size: usize,
+    // Whether an ls of this directory has been seen, after which every further ls must agree with it.
+    listed: bool,
 }
 
 impl Directory
 {
     pub fn new(name: String) -> Directory
     {
-        Directory {name, children: Vec::new(), parent: None, files: Vec::new(), size: 0 }
+        Directory {name, children: Vec::new(), parent: None, files: Vec::new(), size: 0, listed: false }
     }
 
     pub fn size(&self) -> usize
@@ -111,52 +177,72 @@ impl Directory
         return self.name.as_str();
     }
 
+    pub fn children(&self) -> &[DirId]
+    {
+        &self.children
+    }
+
+    pub fn parent(&self) -> Option<DirId>
+    {
+        self.parent
+    }
+
+    pub fn files(&self) -> &[File]
+    {
+        &self.files
+    }
+
     pub fn add_file(&mut self, file: File)
     {
 
         self.size += file.size;
         self.files.push(file);
-
-        // self.recalculate_size();
     }
 
 }
 
+// Every directory lives in one arena and refers to the others by DirId, so the whole structure is plain owned data.
+// Directory sizes are kept up to date as entries come and go by walking the ancestors of whatever changed.
 pub struct Filesystem
 {
-    directories: Vec<Rc<RefCell<Directory>>>,
-    cwd_index: usize
+    directories: Vec<Directory>,
+    cwd_id: DirId,
+    listing: Option<Listing>,
+}
+
+// The ls currently being read: which directory, and the names it has shown so far.
+struct Listing
+{
+    dir: DirId,
+    files: HashSet<String>,
+    dirs: HashSet<String>,
 }
 
 impl Filesystem 
 {
     pub fn new() -> Filesystem
     {
-        let root = Rc::new(RefCell::new(Directory::new(String::from("/"))));
-        let mut dirs = Vec::new();
-        dirs.push(root.clone());
-        Filesystem { directories: dirs, cwd_index: 0}
+        let root = Directory::new(String::from("/"));
+        Filesystem { directories: vec![root], cwd_id: ROOT, listing: None }
     }
 
     pub fn breadth_first_filter<F>(&self, cmp: F) -> Vec<(String, usize)>
     where F: Fn(usize) -> bool
     {
-        let mut to_visit = Vec::<usize>::new();
-        to_visit.push(0);
+        let mut to_visit = VecDeque::from([ROOT]);
         
         let mut matches = Vec::<(String, usize)>::new();
 
-        while !to_visit.is_empty()
+        while let Some(next) = to_visit.pop_front()
         {
-            let next = to_visit.remove(0);
-            let dir = self.directories.get(next).unwrap().as_ref().borrow();
+            let dir = self.directory(next);
 
             if cmp(dir.size())
             {
                 matches.push((dir.name.clone(), dir.size))
             }
 
-            to_visit.extend_from_slice(&dir.children);
+            to_visit.extend(dir.children.iter().copied());
         }
 
         return matches;
@@ -164,142 +250,555 @@ impl Filesystem
 
     pub fn breadth_first_filter_size(&self, max_size: usize) -> Vec<(String, usize)>
     {
-        let mut to_visit = Vec::<usize>::new();
-        to_visit.push(0);
-        
-        let mut matches = Vec::<(String, usize)>::new();
+        self.breadth_first_filter(|size| size <= max_size)
+    }
+
+    pub fn is_dir(&self, name: &str) -> bool
+    {
+        self.child_dir(self.cwd_id, name).is_some()
+    }
+
+    pub fn create_dir(&mut self, child: Directory)
+    {
+        self.create_dir_in(self.cwd_id, child);
+    }
+
+    fn create_dir_in(&mut self, parent: DirId, child: Directory)
+    {
+        let id = DirId(self.directories.len());
+        self.directories.push(child);
+        self.attach_dir(parent, id);
+    }
+
+    pub fn add_file(&mut self, file: File)
+    {
+        self.add_file_in(self.cwd_id, file);
+    }
+
+    fn add_file_in(&mut self, dir: DirId, file: File)
+    {
+        let size = file.size;
+        self.dir_mut(dir).files.push(file);
+        self.grow(dir, size);
+    }
+
+    // Recomputes every size from scratch in one post-order pass.  Only needed after directories have been changed
+    // behind the filesystem's back; everything done through Filesystem keeps the sizes right as it goes.
+    pub fn update_sizes(&mut self)
+    {
+        let mut order = Vec::new();
+        let mut to_visit = vec![ROOT];
+        while let Some(id) = to_visit.pop()
+        {
+            order.push(id);
+            to_visit.extend(self.directory(id).children.iter().copied());
+        }
 
-        while !to_visit.is_empty()
+        // Every directory comes after its parent in order, so going backwards sees children first.
+        for id in order.into_iter().rev()
         {
-            let next = to_visit.remove(0);
-            let dir = self.directories.get(next).unwrap().as_ref().borrow();
+            let dir = self.directory(id);
+            let size = dir.files.iter().map(|file| file.size).sum::<usize>() + dir.children.iter().map(|child| self.directory(*child).size).sum::<usize>();
+            self.dir_mut(id).size = size;
+        }
+    }
 
-            if dir.size <= max_size
-            {
-                matches.push((dir.name.clone(), dir.size))
-            }
+    fn grow(&mut self, from: DirId, amount: usize)
+    {
+        let mut current = Some(from);
+        while let Some(id) = current
+        {
+            let dir = self.dir_mut(id);
+            dir.size += amount;
+            current = dir.parent;
+        }
+    }
 
-            to_visit.extend_from_slice(&dir.children);
+    fn shrink(&mut self, from: DirId, amount: usize)
+    {
+        let mut current = Some(from);
+        while let Some(id) = current
+        {
+            let dir = self.dir_mut(id);
+            dir.size -= amount;
+            current = dir.parent;
         }
+    }
 
-        return matches;
+    pub fn begin_listing(&mut self)
+    {
+        self.listing = Some(Listing { dir: self.cwd_id, files: HashSet::new(), dirs: HashSet::new() });
     }
 
-    pub fn is_dir(&self, name: &str) -> bool
+    fn current_listing(&mut self) -> &mut Listing
+    {
+        if self.listing.as_ref().is_none_or(|listing| listing.dir != self.cwd_id)
+        {
+            // Entries turned up without an ls in front of them.  Read them as a listing all the same.
+            self.begin_listing();
+        }
+
+        self.listing.as_mut().unwrap()
+    }
+
+    pub fn child_path(&self, id: DirId, name: &str) -> String
+    {
+        let parent = self.path_of(id);
+        if parent == "/" { format!("/{}", name) } else { format!("{}/{}", parent, name) }
+    }
+
+    fn child_dir(&self, id: DirId, name: &str) -> Option<DirId>
     {
-        if let Some(cwd) = self.directories.get(self.cwd_index)
+        self.directory(id).children.iter().find(|child| self.directory(**child).name == name).copied()
+    }
+
+    fn attach_dir(&mut self, parent: DirId, child: DirId)
+    {
+        self.dir_mut(child).parent = Some(parent);
+        self.dir_mut(parent).children.push(child);
+        self.grow(parent, self.directory(child).size);
+    }
+
+    fn detach_dir(&mut self, parent: DirId, child: DirId)
+    {
+        // The directory stays in the arena, but nothing can reach it any more.
+        self.dir_mut(parent).children.retain(|id| *id != child);
+        self.shrink(parent, self.directory(child).size);
+    }
+
+    fn remove_file(&mut self, dir: DirId, name: &str)
+    {
+        let removed: usize = self.directory(dir).files.iter().filter(|file| file.name == name).map(|file| file.size).sum();
+        self.dir_mut(dir).files.retain(|file| file.name != name);
+        self.shrink(dir, removed);
+    }
+
+    pub fn list_file(&mut self, file: File) -> Vec<FsWarning>
+    {
+        let cwd = self.cwd_id;
+        let path = self.child_path(cwd, &file.name);
+        let mut warnings = Vec::new();
+
+        if !self.current_listing().files.insert(file.name.clone())
+        {
+            warnings.push(FsWarning::DuplicateEntry { path: path.clone() });
+        }
+
+        if let Some(child) = self.child_dir(cwd, &file.name)
+        {
+            warnings.push(FsWarning::KindChanged { path: path.clone() });
+            self.detach_dir(cwd, child);
+        }
+
+        match self.directory(cwd).files.iter().position(|existing| existing.name == file.name)
         {
-            for child_index in &cwd.as_ref().borrow().children
+            Some(position) =>
             {
-                if let Some(dir) = self.directories.get(*child_index)
+                let old = self.directory(cwd).files[position].size;
+                if old != file.size
                 {
-                    if dir.as_ref().borrow().name == name { return  true; }
+                    warnings.push(FsWarning::ConflictingSize { path, old, new: file.size });
+                    self.dir_mut(cwd).files[position].size = file.size;
+                    self.shrink(cwd, old);
+                    self.grow(cwd, file.size);
                 }
-            }
+            },
+            None => self.add_file(file),
         }
 
-        false
+        warnings
     }
 
-    pub fn create_dir(&mut self, mut child: Directory)
+    pub fn list_dir(&mut self, name: String) -> Vec<FsWarning>
     {
-        child.parent = Some(self.cwd_index);
-        self.directories.push(Rc::new(RefCell::new(child)));
+        let cwd = self.cwd_id;
+        let path = self.child_path(cwd, &name);
+        let mut warnings = Vec::new();
+
+        if !self.current_listing().dirs.insert(name.clone())
+        {
+            warnings.push(FsWarning::DuplicateEntry { path: path.clone() });
+        }
+
+        if self.directory(cwd).files.iter().any(|file| file.name == name)
+        {
+            warnings.push(FsWarning::KindChanged { path });
+            self.remove_file(cwd, &name);
+        }
+
+        if self.child_dir(cwd, &name).is_none()
+        {
+            self.create_dir(Directory::new(name));
+        }
+
+        warnings
+    }
+
+    // Closes the current ls, if there is one.  If the directory had been listed before, anything the earlier listing
+    // showed that this one did not is taken to be gone.
+    pub fn end_listing(&mut self) -> Vec<FsWarning>
+    {
+        let listing = match self.listing.take()
+        {
+            Some(listing) => listing,
+            None => return Vec::new(),
+        };
 
-        if let Some(cwd) = self.directories.get(self.cwd_index)
+        let mut warnings = Vec::new();
+
+        if self.directory(listing.dir).listed
         {
-            cwd.borrow_mut().children.push(self.directories.len() - 1);
+            let dir = self.directory(listing.dir);
+            let vanished_files: Vec<String> = dir.files.iter().filter(|file| !listing.files.contains(&file.name)).map(|file| file.name.clone()).collect();
+            let vanished_dirs: Vec<DirId> = dir.children.iter().filter(|child| !listing.dirs.contains(&self.directory(**child).name)).copied().collect();
+
+            for name in vanished_files
+            {
+                warnings.push(FsWarning::Vanished { path: self.child_path(listing.dir, &name) });
+                self.remove_file(listing.dir, &name);
+            }
+            for child in vanished_dirs
+            {
+                warnings.push(FsWarning::Vanished { path: self.path_of(child) });
+                self.detach_dir(listing.dir, child);
+            }
         }
+        self.dir_mut(listing.dir).listed = true;
+
+        warnings
     }
 
-    pub fn update_sizes(&self)
+    // Splits a path naming an entry into the directory holding it and its name, e.g. a/b/c.txt into a/b and c.txt.
+    fn split_path<'a>(&self, path: &'a str) -> Result<(DirId, &'a str), ShellError>
     {
-        self.recalculate_size(self.cwd_index);
+        let trimmed = path.trim_end_matches('/');
+        let (parent, name) = match trimmed.rsplit_once('/')
+        {
+            Some(("", name)) => (ROOT, name),
+            Some((parent, name)) => (self.resolve(parent).map_err(ShellError::Path)?, name),
+            None => (self.cwd_id, trimmed),
+        };
 
-        let mut cwd = self.directories.get(self.cwd_index).unwrap();
+        if name.is_empty() || name == "." || name == ".."
+        {
+            return Err(ShellError::NoName { path: String::from(path) });
+        }
 
-        let mut parent_index = cwd.as_ref().borrow().parent;
+        Ok((parent, name))
+    }
 
-        while parent_index.is_some() 
+    fn ensure_free(&self, dir: DirId, name: &str) -> Result<(), ShellError>
+    {
+        if self.child_dir(dir, name).is_some() || self.directory(dir).files.iter().any(|file| file.name == name)
         {
-            self.recalculate_size(parent_index.unwrap());
-            cwd = self.directories.get(parent_index.unwrap()).unwrap();
-            parent_index = cwd.as_ref().borrow().parent;
+            return Err(ShellError::AlreadyExists { path: self.child_path(dir, name) });
         }
+
+        Ok(())
     }
 
-    fn recalculate_size(&self, index: usize)
+    // Whether id is ancestor itself or somewhere below it.
+    fn is_within(&self, id: DirId, ancestor: DirId) -> bool
     {
-        let mut new_size: usize = 0;
-        if let Some(dir) = self.directories.get(index)
+        let mut current = Some(id);
+        while let Some(dir) = current
         {
-            for file in &dir.as_ref().borrow().files
+            if dir == ancestor
             {
-                new_size += file.size;
+                return true;
             }
+            current = self.directory(dir).parent;
+        }
+
+        false
+    }
+
+    pub fn mkdir(&mut self, path: &str) -> Result<(), ShellError>
+    {
+        let (parent, name) = self.split_path(path)?;
+        self.ensure_free(parent, name)?;
+
+        self.create_dir_in(parent, Directory::new(String::from(name)));
+        Ok(())
+    }
 
-            for child_index in &dir.as_ref().borrow().children
+    // Without recursive only files can go.  The cwd and the directories above it can never be removed.
+    pub fn rm(&mut self, path: &str, recursive: bool) -> Result<(), ShellError>
+    {
+        let (parent, name) = self.split_path(path)?;
+
+        if let Some(child) = self.child_dir(parent, name)
+        {
+            if !recursive
             {
-                new_size += self.directories.get(*child_index).unwrap().as_ref().borrow().size;
+                return Err(ShellError::IsADirectory { path: self.path_of(child) });
+            }
+            if self.is_within(self.cwd_id, child)
+            {
+                return Err(ShellError::Busy { path: self.path_of(child) });
             }
 
-            dir.borrow_mut().size = new_size;
+            self.detach_dir(parent, child);
         }
+        else if self.directory(parent).files.iter().any(|file| file.name == name)
+        {
+            self.remove_file(parent, name);
+        }
+        else
+        {
+            return Err(ShellError::NoSuchEntry { path: self.child_path(parent, name) });
+        }
+
+        Ok(())
     }
 
-    pub fn cwd(&mut self, to: &str)
+    // Moving onto an existing directory puts the entry inside it under its own name; anything else is a rename.
+    // Nothing is ever overwritten.
+    pub fn mv(&mut self, from: &str, to: &str) -> Result<(), ShellError>
     {
-        match to 
+        let (from_parent, from_name) = self.split_path(from)?;
+        let (to_parent, to_name) = match self.resolve(to)
         {
-            "/" => 
+            Ok(dir) => (dir, String::from(from_name)),
+            Err(_) =>
             {
-                self.cwd_index = 0;
-                // self.cwd = self.directories.get(0).unwrap().clone();
+                let (parent, name) = self.split_path(to)?;
+                (parent, String::from(name))
             },
-            ".." => 
+        };
+
+        if let Some(child) = self.child_dir(from_parent, from_name)
+        {
+            if self.is_within(to_parent, child)
             {
-                if let Some(cwd) = self.directories.get(self.cwd_index)
-                {
-                    if let Some(parent_index) = cwd.as_ref().borrow().parent
-                    {
-                        // temp = self.directories.get(parent_index).unwrap_or(&self.directories.get(0).unwrap().clone()).clone()
-                        self.cwd_index = parent_index;
-                    }
-                    else
-                    {
-                        return;
-                    }                    
-                }
+                return Err(ShellError::IntoItself { from: self.path_of(child), to: self.child_path(to_parent, &to_name) });
+            }
+            self.ensure_free(to_parent, &to_name)?;
+
+            self.detach_dir(from_parent, child);
+            self.dir_mut(child).name = to_name;
+            self.attach_dir(to_parent, child);
+        }
+        else if let Some(position) = self.directory(from_parent).files.iter().position(|file| file.name == from_name)
+        {
+            self.ensure_free(to_parent, &to_name)?;
+
+            let mut file = self.dir_mut(from_parent).files.remove(position);
+            self.shrink(from_parent, file.size);
+            file.name = to_name;
+            self.add_file_in(to_parent, file);
+        }
+        else
+        {
+            return Err(ShellError::NoSuchEntry { path: self.child_path(from_parent, from_name) });
+        }
+
+        Ok(())
+    }
+
+    // Creates the file, or gives an existing one the new size.
+    pub fn touch(&mut self, path: &str, size: usize) -> Result<(), ShellError>
+    {
+        let (dir, name) = self.split_path(path)?;
+
+        if let Some(child) = self.child_dir(dir, name)
+        {
+            return Err(ShellError::IsADirectory { path: self.path_of(child) });
+        }
+
+        match self.directory(dir).files.iter().position(|file| file.name == name)
+        {
+            Some(position) =>
+            {
+                let old = self.directory(dir).files[position].size;
+                self.dir_mut(dir).files[position].size = size;
+                self.shrink(dir, old);
+                self.grow(dir, size);
             },
-            _ => 
+            None => self.add_file_in(dir, File { name: String::from(name), size }),
+        }
+
+        Ok(())
+    }
+
+    // Follows a path the way a shell would: absolute or relative, any number of segments, with . and .. segments and
+    // redundant slashes allowed.  .. at the root stays at the root.  Returns the directory it lands on.
+    pub fn resolve(&self, path: &str) -> Result<DirId, PathError>
+    {
+        if path.is_empty()
+        {
+            return Err(PathError::Empty);
+        }
+
+        let mut id = if path.starts_with('/') { ROOT } else { self.cwd_id };
+
+        for segment in path.split('/').filter(|segment| !segment.is_empty())
+        {
+            let dir = self.directory(id);
+            id = match segment
             {
-                if let Some(cwd) = self.directories.get(self.cwd_index)
+                "." => id,
+                ".." => dir.parent.unwrap_or(id),
+                _ =>
                 {
-                    for child_index in &cwd.as_ref().borrow().children
+                    match self.child_dir(id, segment)
                     {
-                        if let Some(dir) = self.directories.get(*child_index)
+                        Some(child) => child,
+                        None if dir.files.iter().any(|file| file.name == segment) =>
                         {
-                            if dir.as_ref().borrow().name == to
-                            {
-                                self.cwd_index = *child_index;
-                                break;
-                            }
-                        }
+                            return Err(PathError::NotADirectory { path: String::from(path), segment: String::from(segment) });
+                        },
+                        None => return Err(PathError::NoSuchDirectory { path: String::from(path), segment: String::from(segment) }),
                     }
-                }
+                },
+            };
+        }
+
+        Ok(id)
+    }
+
+    pub fn cwd(&mut self, to: &str) -> Result<(), PathError>
+    {
+        self.cwd_id = self.resolve(to)?;
+        Ok(())
+    }
+
+    // Absolute path of a directory, e.g. /a/b.
+    pub fn path_of(&self, id: DirId) -> String
+    {
+        let mut names = Vec::new();
+        let mut current = Some(id);
+        while let Some(dir_id) = current
+        {
+            let dir = self.directory(dir_id);
+            if dir.parent.is_some()
+            {
+                names.push(dir.name.as_str());
             }
+            current = dir.parent;
+        }
+
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    pub fn cwd_path(&self) -> String
+    {
+        self.path_of(self.cwd_id)
+    }
+
+    pub fn cwd_id(&self) -> DirId
+    {
+        self.cwd_id
+    }
+
+    pub fn directory(&self, id: DirId) -> &Directory
+    {
+        match self.directories.get(id.0)
+        {
+            Some(dir) => dir,
+            None => panic!("There is no directory {:?} in this filesystem.", id),
         }
     }
 
-    pub fn get_cwd(&self) -> Rc<RefCell<Directory>>
+    fn dir_mut(&mut self, id: DirId) -> &mut Directory
     {
-        if let Some(cwd) = self.directories.get(self.cwd_index)
+        match self.directories.get_mut(id.0)
         {
-            cwd.clone()
+            Some(dir) => dir,
+            None => panic!("There is no directory {:?} in this filesystem.", id),
         }
-        else
+    }
+
+    pub fn get_cwd(&self) -> &Directory
+    {
+        self.directory(self.cwd_id)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PathError
+{
+    Empty,
+    // segment is the part of the path that could not be found.
+    NoSuchDirectory { path: String, segment: String },
+    NotADirectory { path: String, segment: String },
+}
+
+impl fmt::Display for PathError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            PathError::Empty => write!(f, "an empty path leads nowhere"),
+            PathError::NoSuchDirectory { path, segment } => write!(f, "{}: there is no directory {}", path, segment),
+            PathError::NotADirectory { path, segment } => write!(f, "{}: {} is a file, not a directory", path, segment),
+        }
+    }
+}
+
+// Why a cd, mkdir, rm, mv or touch could not be carried out.
+#[derive(Debug, PartialEq)]
+pub enum ShellError
+{
+    Path(PathError),
+    // The path ends in /, . or .. rather than the name of an entry.
+    NoName { path: String },
+    NoSuchEntry { path: String },
+    AlreadyExists { path: String },
+    IsADirectory { path: String },
+    // The directory is the cwd or one of the directories above it.
+    Busy { path: String },
+    IntoItself { from: String, to: String },
+}
+
+impl fmt::Display for ShellError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
         {
-            panic!("There is no actual directory entry for the current working directory.");
+            ShellError::Path(err) => write!(f, "{}", err),
+            ShellError::NoName { path } => write!(f, "{} does not name a file or directory", path),
+            ShellError::NoSuchEntry { path } => write!(f, "there is no {}", path),
+            ShellError::AlreadyExists { path } => write!(f, "{} already exists", path),
+            ShellError::IsADirectory { path } => write!(f, "{} is a directory", path),
+            ShellError::Busy { path } => write!(f, "{} holds the current directory", path),
+            ShellError::IntoItself { from, to } => write!(f, "{} cannot be moved inside itself to {}", from, to),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FsWarning
+{
+    // The same name shown twice in a single ls.
+    DuplicateEntry { path: String },
+    // A later ls gave a file a different size.  The later size is the one kept.
+    ConflictingSize { path: String, old: usize, new: usize },
+    // A name went from file to directory, or the other way round, between listings.
+    KindChanged { path: String },
+    // A later ls of a directory no longer shows something an earlier one did.
+    Vanished { path: String },
+    // A cd, mkdir, rm, mv or touch that could not be carried out, and so left the filesystem as it was.
+    Rejected { command: String, reason: ShellError },
+    // An ls entry that came after a cd went nowhere, so there is no telling which directory it belongs in.
+    Dropped { entry: String },
+}
+
+impl fmt::Display for FsWarning
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            FsWarning::DuplicateEntry { path } => write!(f, "{} is listed more than once in the same ls", path),
+            FsWarning::ConflictingSize { path, old, new } => write!(f, "{} was listed as {} bytes and later as {} bytes", path, old, new),
+            FsWarning::KindChanged { path } => write!(f, "{} changed between being a file and a directory", path),
+            FsWarning::Vanished { path } => write!(f, "{} is missing from a later ls of its directory", path),
+            FsWarning::Rejected { command, reason } => write!(f, "{} was not carried out: {}", command, reason),
+            FsWarning::Dropped { entry } => write!(f, "{} was listed while the current directory was unknown", entry),
         }
     }
 }
@@ -325,17 +824,59 @@ impl Shell
             split.next(); // discard prompt
             let command = split.next().unwrap();
             let dir_name = split.next();
+            let second_arg = split.next();
+            // None of the commands take more than two arguments, or any flag other than rm's -r.
+            let extra_args = split.next().is_some();
+            let is_path = |arg: &&str| !arg.starts_with('-');
             
             match command
             {
                 "cd" => 
                 {
-                    return LineType::ChangeDirectory(String::from(dir_name.unwrap()));
+                    // A bare cd has nowhere to go, which the filesystem reports rather than the parser.
+                    return LineType::ChangeDirectory(String::from(dir_name.unwrap_or("")));
                 },
                 "ls" => 
                 {
                     return LineType::List
                 },
+                _ if extra_args => 
+                {
+                    return LineType::UnknownToken
+                },
+                "mkdir" => 
+                {
+                    match (dir_name.filter(is_path), second_arg)
+                    {
+                        (Some(path), None) => return LineType::MakeDirectory(String::from(path)),
+                        _ => return LineType::UnknownToken,
+                    }
+                },
+                "rm" => 
+                {
+                    match (dir_name, second_arg)
+                    {
+                        (Some("-r"), Some(path)) if is_path(&path) => return LineType::Remove((String::from(path), true)),
+                        (Some(path), None) if is_path(&path) => return LineType::Remove((String::from(path), false)),
+                        _ => return LineType::UnknownToken,
+                    }
+                },
+                "mv" => 
+                {
+                    match (dir_name.filter(is_path), second_arg.filter(is_path))
+                    {
+                        (Some(from), Some(to)) => return LineType::Move((String::from(from), String::from(to))),
+                        _ => return LineType::UnknownToken,
+                    }
+                },
+                "touch" => 
+                {
+                    match (dir_name.and_then(|size| size.parse::<usize>().ok()), second_arg.filter(is_path))
+                    {
+                        (Some(size), Some(path)) => return LineType::Touch((String::from(path), size)),
+                        _ => return LineType::UnknownToken,
+                    }
+                },
                 _ => 
                 {
                     return LineType::UnknownToken
@@ -371,6 +912,12 @@ pub enum LineType
     List,
     DirectoryEntry(String),
     FileEntry((String, usize)),
+    MakeDirectory(String),
+    // Path, and whether directories may go too.
+    Remove((String, bool)),
+    Move((String, String)),
+    // Path and size.
+    Touch((String, usize)),
     Noop,
     UnknownToken,
 }
@@ -379,7 +926,7 @@ pub enum LineType
 
 pub mod test
 {
-    use crate::day7::advent::{LineType, Shell, File, Directory};
+    use crate::day7::advent::{fill_fs_checked, LineType, Shell, File, Directory, FsWarning, PathError, ShellError};
 
     use super::Filesystem;
 
@@ -403,10 +950,10 @@ pub mod test
         let a = Directory::new(String::from("a"));
 
         fs.create_dir(a);
-        fs.cwd("a");
-        fs.cwd("..");
+        fs.cwd("a").unwrap();
+        fs.cwd("..").unwrap();
 
-        assert_eq!(fs.get_cwd().borrow().name(), "/");
+        assert_eq!(fs.get_cwd().name(), "/");
     }
 
     #[test]
@@ -418,13 +965,13 @@ pub mod test
         let b = Directory::new(String::from("b"));
 
         fs.create_dir(a);
-        fs.cwd("a");
+        fs.cwd("a").unwrap();
         fs.create_dir(b);
-        fs.cwd("b");
+        fs.cwd("b").unwrap();
 
-        fs.cwd("/");
+        fs.cwd("/").unwrap();
 
-        assert_eq!(fs.get_cwd().borrow().name(), "/");
+        assert_eq!(fs.get_cwd().name(), "/");
     }
 
     #[test]
@@ -438,26 +985,26 @@ pub mod test
         let file_a = File{name: String::from("a_file"), size: 10000};
         let file_b = File {name: String::from("filename"), size: 4321};
 
-        let root_size = fs.get_cwd().borrow().size();
+        let root_size = fs.get_cwd().size();
         let a_size = a.size();
         let b_size = b.size();
 
         a.add_file(file_a);
 
         fs.create_dir(a);
-        fs.cwd("a");
+        fs.cwd("a").unwrap();
         b.add_file(file_b);
         fs.create_dir(b);
 
-        fs.cwd("b");
+        fs.cwd("b").unwrap();
 
         fs.update_sizes();
 
-        assert_eq!(b_size + 4321, fs.get_cwd().borrow().size());
-        fs.cwd("..");
-        assert_eq!(a_size + 14321, fs.get_cwd().borrow().size());
-        fs.cwd("..");
-        assert_eq!(root_size + 14321, fs.get_cwd().borrow().size());
+        assert_eq!(b_size + 4321, fs.get_cwd().size());
+        fs.cwd("..").unwrap();
+        assert_eq!(a_size + 14321, fs.get_cwd().size());
+        fs.cwd("..").unwrap();
+        assert_eq!(root_size + 14321, fs.get_cwd().size());
     }
 
     #[test]
@@ -559,4 +1106,212 @@ pub mod test
         }
     }
 
+
+    // The transcript from the puzzle description.
+    pub fn sample<'a>() -> Vec<&'a str>
+    {
+        vec![
+            "$ cd /", "$ ls", "dir a", "14848514 b.txt", "8504156 c.dat", "dir d",
+            "$ cd a", "$ ls", "dir e", "29116 f", "2557 g", "62596 h.lst",
+            "$ cd e", "$ ls", "584 i",
+            "$ cd ..", "$ cd ..", "$ cd d", "$ ls", "4060174 j", "8033020 d.log", "5626152 d.ext", "7214296 k",
+        ]
+    }
+
+    fn nested() -> Filesystem
+    {
+        let mut fs = Filesystem::new();
+        fs.create_dir(Directory::new(String::from("a")));
+        fs.create_dir(Directory::new(String::from("x")));
+        fs.cwd("a").unwrap();
+        fs.create_dir(Directory::new(String::from("b")));
+        fs.create_dir(Directory::new(String::from("c")));
+        fs.add_file(File{name: String::from("notes.txt"), size: 10});
+        fs.cwd("/x").unwrap();
+        fs.create_dir(Directory::new(String::from("y")));
+        fs.cwd("/").unwrap();
+
+        fs
+    }
+
+    #[test]
+    pub fn when_a_filesystem_is_given_a_multi_segment_path_it_follows_every_segment()
+    {
+        let mut fs = nested();
+
+        fs.cwd("a/b/../c").unwrap();
+        assert_eq!(fs.cwd_path(), "/a/c");
+
+        fs.cwd("/x/y").unwrap();
+        assert_eq!(fs.cwd_path(), "/x/y");
+
+        fs.cwd(".//..///../a/./b/").unwrap();
+        assert_eq!(fs.cwd_path(), "/a/b");
+
+        fs.cwd("../../..").unwrap();
+        assert_eq!(fs.cwd_path(), "/");
+    }
+
+    #[test]
+    pub fn when_a_path_cannot_be_resolved_an_error_is_returned_and_cwd_does_not_move()
+    {
+        let mut fs = nested();
+        fs.cwd("a").unwrap();
+
+        assert_eq!(fs.cwd("b/missing/c"), Err(PathError::NoSuchDirectory { path: String::from("b/missing/c"), segment: String::from("missing") }));
+        assert_eq!(fs.cwd("notes.txt"), Err(PathError::NotADirectory { path: String::from("notes.txt"), segment: String::from("notes.txt") }));
+        assert_eq!(fs.cwd(""), Err(PathError::Empty));
+        assert_eq!(fs.cwd_path(), "/a");
+    }
+
+    #[test]
+    pub fn when_a_directory_is_listed_twice_nothing_is_counted_twice()
+    {
+        let transcript = vec!["$ cd /", "$ ls", "dir a", "100 b.txt", "$ cd a", "$ ls", "50 c.txt", "$ cd ..", "$ ls", "dir a", "100 b.txt", "$ cd a", "$ ls", "50 c.txt"];
+
+        let (mut fs, warnings) = fill_fs_checked(transcript);
+
+        assert!(warnings.is_empty());
+        fs.cwd("/").unwrap();
+        assert_eq!(fs.get_cwd().size(), 150);
+        assert_eq!(fs.breadth_first_filter(|_| true).len(), 2);
+    }
+
+    #[test]
+    pub fn when_a_later_listing_disagrees_the_later_listing_wins_and_a_warning_is_raised()
+    {
+        let transcript = vec!["$ cd /", "$ ls", "dir a", "dir gone", "100 b.txt", "7 old.txt", "$ ls", "dir a", "dir a", "120 b.txt", "dir old.txt"];
+
+        let (mut fs, warnings) = fill_fs_checked(transcript);
+
+        assert_eq!(warnings, vec![
+            FsWarning::DuplicateEntry { path: String::from("/a") },
+            FsWarning::ConflictingSize { path: String::from("/b.txt"), old: 100, new: 120 },
+            FsWarning::KindChanged { path: String::from("/old.txt") },
+            FsWarning::Vanished { path: String::from("/gone") },
+        ]);
+        fs.cwd("/").unwrap();
+        assert_eq!(fs.get_cwd().size(), 120);
+        assert!(fs.cwd("gone").is_err());
+    }
+
+    #[test]
+    pub fn when_line_processor_receives_a_mutating_command_it_returns_its_arguments()
+    {
+        assert!(matches!(Shell::line_processor("$ mkdir a/b"), LineType::MakeDirectory(path) if path == "a/b"));
+        assert!(matches!(Shell::line_processor("$ rm -r a"), LineType::Remove((path, true)) if path == "a"));
+        assert!(matches!(Shell::line_processor("$ rm a.txt"), LineType::Remove((path, false)) if path == "a.txt"));
+        assert!(matches!(Shell::line_processor("$ mv a /b"), LineType::Move((from, to)) if from == "a" && to == "/b"));
+        assert!(matches!(Shell::line_processor("$ touch 1024 c.dat"), LineType::Touch((path, 1024)) if path == "c.dat"));
+        assert!(matches!(Shell::line_processor("$ touch big c.dat"), LineType::UnknownToken));
+        assert!(matches!(Shell::line_processor("$ rm -r"), LineType::UnknownToken));
+        assert!(matches!(Shell::line_processor("$ mv a"), LineType::UnknownToken));
+        assert!(matches!(Shell::line_processor("$ mkdir a b"), LineType::UnknownToken));
+        assert!(matches!(Shell::line_processor("$ mkdir -p a/b"), LineType::UnknownToken));
+        assert!(matches!(Shell::line_processor("$ touch 5 a extra"), LineType::UnknownToken));
+        assert!(matches!(Shell::line_processor("$ rm -r a b"), LineType::UnknownToken));
+        assert!(matches!(Shell::line_processor("$ rm -f a"), LineType::UnknownToken));
+        assert!(matches!(Shell::line_processor("$ mv a b c"), LineType::UnknownToken));
+    }
+
+    #[test]
+    pub fn when_entries_are_made_removed_and_moved_every_size_above_them_follows()
+    {
+        let transcript = vec![
+            "$ cd /", "$ ls", "dir a", "100 b.txt",
+            "$ cd a", "$ ls", "dir e", "50 c.txt",
+            "$ cd e", "$ ls", "7 i",
+            "$ cd /",
+            "$ mkdir x/y",
+            "$ mkdir x",
+            "$ touch 30 x/new.dat",
+            "$ touch 60 b.txt",
+            "$ mv a/e x",
+            "$ mv a/c.txt x/e/renamed.txt",
+            "$ rm a",
+            "$ rm -r a",
+        ];
+
+        let (mut fs, warnings) = fill_fs_checked(transcript);
+
+        assert_eq!(warnings, vec![
+            FsWarning::Rejected { command: String::from("$ mkdir x/y"), reason: ShellError::Path(PathError::NoSuchDirectory { path: String::from("x"), segment: String::from("x") }) },
+            FsWarning::Rejected { command: String::from("$ rm a"), reason: ShellError::IsADirectory { path: String::from("/a") } },
+        ]);
+        assert_eq!(fs.get_cwd().size(), 147);
+        assert!(fs.cwd("/a").is_err());
+        fs.cwd("/x/e").unwrap();
+        assert_eq!(fs.get_cwd().size(), 57);
+        assert_eq!(fs.directory(fs.get_cwd().parent().unwrap()).size(), 87);
+
+        let before: Vec<usize> = fs.breadth_first_filter(|_| true).into_iter().map(|(_, size)| size).collect();
+        fs.update_sizes();
+        let after: Vec<usize> = fs.breadth_first_filter(|_| true).into_iter().map(|(_, size)| size).collect();
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    pub fn when_a_mutating_command_makes_no_sense_the_filesystem_is_left_alone()
+    {
+        let mut fs = nested();
+        fs.cwd("/a/b").unwrap();
+
+        assert_eq!(fs.rm("/a", true), Err(ShellError::Busy { path: String::from("/a") }));
+        assert_eq!(fs.rm("/missing", false), Err(ShellError::NoSuchEntry { path: String::from("/missing") }));
+        assert_eq!(fs.rm("/", true), Err(ShellError::NoName { path: String::from("/") }));
+        assert_eq!(fs.mv("/a", "/a/c"), Err(ShellError::IntoItself { from: String::from("/a"), to: String::from("/a/c/a") }));
+        assert_eq!(fs.mv("/x", "/a/notes.txt"), Err(ShellError::AlreadyExists { path: String::from("/a/notes.txt") }));
+        assert_eq!(fs.mkdir("/a/c"), Err(ShellError::AlreadyExists { path: String::from("/a/c") }));
+        assert_eq!(fs.touch("..", 5), Err(ShellError::NoName { path: String::from("..") }));
+
+        fs.cwd("/").unwrap();
+        assert_eq!(fs.get_cwd().size(), 10);
+        assert_eq!(fs.breadth_first_filter(|_| true).len(), 6);
+    }
+
+    #[test]
+    pub fn when_a_transcript_changes_into_a_directory_that_is_gone_its_listing_is_dropped_until_the_next_cd()
+    {
+        let transcript = vec!["$ cd /", "$ ls", "dir a", "100 b.txt", "$ rm -r a", "$ cd a", "$ ls", "50 c.txt", "$ cd /", "$ ls", "100 b.txt"];
+
+        let (fs, warnings) = fill_fs_checked(transcript);
+
+        assert_eq!(warnings, vec![
+            FsWarning::Rejected { command: String::from("$ cd a"), reason: ShellError::Path(PathError::NoSuchDirectory { path: String::from("a"), segment: String::from("a") }) },
+            FsWarning::Dropped { entry: String::from("50 c.txt") },
+        ]);
+        assert_eq!(fs.cwd_path(), "/");
+        assert_eq!(fs.get_cwd().size(), 100);
+        assert_eq!(fs.get_cwd().files().len(), 1);
+    }
+
+    #[test]
+    pub fn a_bare_cd_is_rejected_with_a_warning()
+    {
+        let (fs, warnings) = fill_fs_checked(vec!["$ cd /", "$ ls", "dir a", "$ cd"]);
+
+        assert_eq!(warnings, vec![FsWarning::Rejected { command: String::from("$ cd"), reason: ShellError::Path(PathError::Empty) }]);
+        assert_eq!(fs.cwd_path(), "/");
+    }
+
+    #[test]
+    pub fn a_path_error_reads_the_same_whichever_command_hit_it()
+    {
+        let (_, warnings) = fill_fs_checked(vec!["$ cd /", "$ mkdir x/y"]);
+
+        assert_eq!(warnings[0].to_string(), "$ mkdir x/y was not carried out: x: there is no directory x");
+    }
+
+    #[test]
+    pub fn a_filesystem_can_be_handed_to_another_thread()
+    {
+        fn assert_send<T: Send>() {}
+        assert_send::<Filesystem>();
+
+        let (fs, _) = fill_fs_checked(vec!["$ cd /", "$ ls", "dir a", "100 b.txt", "$ cd a", "$ ls", "50 c.txt"]);
+        let total = std::thread::spawn(move || fs.get_cwd().size()).join().unwrap();
+
+        assert_eq!(total, 50);
+    }
+
 }
\ No newline at end of file
@@ -0,0 +1,334 @@
+use std::collections::{BTreeMap, HashMap};
+
+use log::debug;
+
+use super::advent::{DirId, Filesystem, ROOT};
+
+#[derive(Debug, Clone, Copy)]
+pub struct DiskSpec
+{
+    pub total: usize,
+    // Free space the update needs.
+    pub needed: usize,
+}
+
+impl Default for DiskSpec
+{
+    fn default() -> DiskSpec
+    {
+        DiskSpec { total: 70000000, needed: 30000000 }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Objective
+{
+    // Delete as little data as possible, however many directories that takes.
+    MinTotal,
+    // Delete as few directories as possible, and among those as little data as possible.
+    MinCount,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CleanupPlan
+{
+    pub used: usize,
+    pub free: usize,
+    // Bytes that have to be freed to meet the spec.
+    pub target: usize,
+    // (absolute path, size) of every directory to delete.  None of them sit inside another.
+    pub delete: Vec<(String, usize)>,
+    pub freed: usize,
+    pub explanation: Vec<String>,
+}
+
+// Every way of deleting a set of non-nested directories from one subtree, boiled down to what matters: for each total
+// below the target the fewest directories that reach it, and for each directory count the smallest total at or above
+// the target.  Adding more to a set that already reaches the target never helps, so nothing bigger is kept.
+#[derive(Debug, Clone, Default)]
+struct Choices
+{
+    // (freed, fewest directories), sorted by freed.
+    below: Vec<(usize, usize)>,
+    above: BTreeMap<usize, usize>,
+}
+
+impl Choices
+{
+    fn nothing() -> Choices
+    {
+        Choices { below: vec![(0, 0)], above: BTreeMap::new() }
+    }
+
+    fn insert(&mut self, freed: usize, count: usize, target: usize)
+    {
+        if freed < target
+        {
+            match self.below.binary_search_by_key(&freed, |(below_freed, _)| *below_freed)
+            {
+                Ok(position) => self.below[position].1 = usize::min(self.below[position].1, count),
+                Err(position) => self.below.insert(position, (freed, count)),
+            }
+        }
+        else
+        {
+            let smallest = self.above.entry(count).or_insert(freed);
+            *smallest = usize::min(*smallest, freed);
+        }
+    }
+
+    fn has(&self, freed: usize, count: usize, target: usize) -> bool
+    {
+        if freed < target
+        {
+            self.below.binary_search(&(freed, count)).is_ok()
+        }
+        else
+        {
+            self.above.get(&count) == Some(&freed)
+        }
+    }
+
+    fn states(&self) -> impl Iterator<Item = (usize, usize)> + '_
+    {
+        self.below.iter().copied().chain(self.above.iter().map(|(count, freed)| (*freed, *count)))
+    }
+
+    // Every pairing of a choice from self with a choice from other.  Sets that already reach the target only pair up
+    // with deleting nothing from the other side.  Totals below the target are gathered in scratch, indexed by total,
+    // which is far quicker than hashing every pair.
+    fn combine(&self, other: &Choices, target: usize, scratch: &mut Vec<usize>) -> Choices
+    {
+        let mut combined = Choices::default();
+        let reach = usize::min(target, self.below.last().unwrap().0 + other.below.last().unwrap().0 + 1);
+        if scratch.len() < reach
+        {
+            scratch.resize(reach, usize::MAX);
+        }
+
+        for (freed, count) in self.below.iter()
+        {
+            // other.below is sorted by freed, so a pair that reaches the target is only worth keeping if it uses fewer
+            // directories than every earlier pair that did.
+            let mut fewest_above = usize::MAX;
+            for (other_freed, other_count) in other.below.iter()
+            {
+                let total = freed + other_freed;
+                if total < target
+                {
+                    scratch[total] = usize::min(scratch[total], count + other_count);
+                }
+                else if count + other_count < fewest_above
+                {
+                    fewest_above = count + other_count;
+                    combined.insert(total, fewest_above, target);
+                }
+            }
+        }
+
+        for (freed, fewest) in scratch.iter_mut().enumerate().take(reach)
+        {
+            if *fewest != usize::MAX
+            {
+                combined.below.push((freed, *fewest));
+                *fewest = usize::MAX;
+            }
+        }
+
+        for (count, freed) in self.above.iter().chain(other.above.iter())
+        {
+            combined.insert(*freed, *count, target);
+        }
+
+        combined
+    }
+}
+
+struct Planner<'a>
+{
+    fs: &'a Filesystem,
+    target: usize,
+    // For every directory: choices using none of its children, then the first child, the first two, ... and last
+    // the choices for the whole subtree, the directory itself included.
+    stages: HashMap<DirId, Vec<Choices>>,
+    scratch: Vec<usize>,
+}
+
+impl<'a> Planner<'a>
+{
+    fn solve(&mut self, id: DirId)
+    {
+        let dir = self.fs.directory(id);
+
+        let mut stages = vec![Choices::nothing()];
+        for child in dir.children()
+        {
+            self.solve(*child);
+            let next = stages.last().unwrap().combine(self.stages[child].last().unwrap(), self.target, &mut self.scratch);
+            stages.push(next);
+        }
+
+        // The root itself can't be deleted.
+        if id != ROOT
+        {
+            let mut whole = stages.last().unwrap().clone();
+            whole.insert(dir.size(), 1, self.target);
+            stages.push(whole);
+        }
+
+        self.stages.insert(id, stages);
+    }
+
+    // Works back from a choice recorded for a subtree to the directories that make it up.
+    fn unwind(&self, id: DirId, freed: usize, count: usize, chosen: &mut Vec<DirId>)
+    {
+        let dir = self.fs.directory(id);
+
+        if id != ROOT && count == 1 && freed == dir.size()
+        {
+            chosen.push(id);
+            return;
+        }
+
+        let stages = &self.stages[&id];
+        let (mut freed, mut count) = (freed, count);
+
+        for (stage, child) in dir.children().iter().enumerate().rev()
+        {
+            let child_choices = self.stages[child].last().unwrap();
+            let split = child_choices.states().find(|(child_freed, child_count)|
+            {
+                *child_freed <= freed && *child_count <= count && stages[stage].has(freed - child_freed, count - child_count, self.target)
+            });
+
+            match split
+            {
+                Some((child_freed, child_count)) =>
+                {
+                    if child_count > 0
+                    {
+                        self.unwind(*child, child_freed, child_count, chosen);
+                    }
+                    freed -= child_freed;
+                    count -= child_count;
+                },
+                None => panic!("The cleanup plan for {} cannot be traced back.", self.fs.path_of(id)),
+            }
+        }
+    }
+}
+
+fn describe_set(fs: &Filesystem, chosen: &[DirId]) -> String
+{
+    let paths: Vec<String> = chosen.iter().map(|id| fs.path_of(*id)).collect();
+    paths.join(", ")
+}
+
+// Finds the set of directories to delete that frees enough space for the spec, best under the given objective.  The
+// search goes over every set of directories none of which contains another, one subtree at a time.  Returns None when
+// even deleting everything below the root would not free enough.
+pub fn plan_cleanup(fs: &Filesystem, spec: &DiskSpec, objective: Objective) -> Option<CleanupPlan>
+{
+    let used = fs.directory(ROOT).size();
+    let free = spec.total.saturating_sub(used);
+    let target = spec.needed.saturating_sub(free);
+
+    let mut explanation = vec![format!("The disk holds {} with {} used, leaving {} free.  The update needs {}, so at least {} has to go.", spec.total, used, free, spec.needed, target)];
+
+    if target == 0
+    {
+        explanation.push(String::from("There is already enough free space, nothing needs deleting."));
+        return Some(CleanupPlan { used, free, target, delete: Vec::new(), freed: 0, explanation });
+    }
+
+    let mut planner = Planner { fs, target, stages: HashMap::new(), scratch: Vec::new() };
+    planner.solve(ROOT);
+
+    let root_choices = planner.stages[&ROOT].last().unwrap();
+    debug!("{} choices below the target, {} at or above it", root_choices.below.len(), root_choices.above.len());
+
+    let least_data = root_choices.above.iter().map(|(count, freed)| (*freed, *count)).min()?;
+    let fewest_dirs = root_choices.above.iter().map(|(count, freed)| (*freed, *count)).next()?;
+
+    let (freed, count) = match objective
+    {
+        Objective::MinTotal => least_data,
+        Objective::MinCount => fewest_dirs,
+    };
+
+    let mut chosen = Vec::new();
+    planner.unwind(ROOT, freed, count, &mut chosen);
+    chosen.sort_by_key(|id| fs.path_of(*id));
+
+    let delete: Vec<(String, usize)> = chosen.iter().map(|id| (fs.path_of(*id), fs.directory(*id).size())).collect();
+
+    explanation.push(format!("Deleting {} frees {}, {} more than needed.", describe_set(fs, &chosen), freed, freed - target));
+    match objective
+    {
+        Objective::MinTotal if fewest_dirs.1 < count =>
+        {
+            explanation.push(format!("It takes {} directories, but no smaller amount of data will do.  The fewest directories that would do is {}, freeing {}.", count, fewest_dirs.1, fewest_dirs.0));
+        },
+        Objective::MinCount if least_data.0 < freed =>
+        {
+            explanation.push(format!("Nothing with fewer directories will do.  Deleting {} directories could free as little as {}.", least_data.1, least_data.0));
+        },
+        _ => explanation.push(String::from("No other set of directories does better by either the amount deleted or the number of directories.")),
+    }
+
+    Some(CleanupPlan { used, free, target, delete, freed, explanation })
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day7::advent::fill_fs_checked;
+    use crate::day7::advent::test::sample;
+
+    use super::{plan_cleanup, DiskSpec, Objective};
+
+    #[test]
+    pub fn the_puzzle_spec_deletes_d()
+    {
+        let (fs, _) = fill_fs_checked(sample());
+
+        let plan = plan_cleanup(&fs, &DiskSpec::default(), Objective::MinCount).unwrap();
+
+        assert_eq!(plan.target, 8381165);
+        assert_eq!(plan.delete, vec![(String::from("/d"), 24933642)]);
+    }
+
+    #[test]
+    pub fn several_small_directories_can_beat_one_big_one()
+    {
+        let transcript = vec![
+            "$ cd /", "$ ls", "dir big", "dir x", "dir y",
+            "$ cd big", "$ ls", "dir inner", "10 b",
+            "$ cd inner", "$ ls", "100 c",
+            "$ cd /x", "$ ls", "60 x",
+            "$ cd /y", "$ ls", "45 y",
+        ];
+        let (fs, _) = fill_fs_checked(transcript);
+        let spec = DiskSpec { total: 300, needed: 190 };
+
+        let least = plan_cleanup(&fs, &spec, Objective::MinTotal).unwrap();
+        let fewest = plan_cleanup(&fs, &spec, Objective::MinCount).unwrap();
+
+        assert_eq!(least.target, 105);
+        assert_eq!(least.delete, vec![(String::from("/x"), 60), (String::from("/y"), 45)]);
+        assert_eq!(least.freed, 105);
+        assert_eq!(fewest.delete, vec![(String::from("/big"), 110)]);
+        assert!(fewest.explanation.last().unwrap().contains("as little as 105"));
+    }
+
+    #[test]
+    pub fn nothing_is_deleted_when_there_is_room_and_none_is_returned_when_there_never_can_be()
+    {
+        let (fs, _) = fill_fs_checked(sample());
+
+        let roomy = plan_cleanup(&fs, &DiskSpec { total: 100000000, needed: 1000 }, Objective::MinTotal).unwrap();
+        assert!(roomy.delete.is_empty());
+
+        assert!(plan_cleanup(&fs, &DiskSpec { total: 48381165, needed: 30000000 }, Objective::MinTotal).is_none());
+    }
+}
@@ -0,0 +1,238 @@
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::advent::{Filesystem, ROOT};
+
+// The plain files and directories directly inside dir, sorted by name, with everything that had to be left out of them
+// added to skipped.  An entry that cannot even be read, say because it vanished mid-walk, is left out too; when the
+// entry is too broken to have a path of its own, dir stands in for it.
+fn read_listing(dir: &Path, skipped: &mut Vec<PathBuf>) -> io::Result<Vec<(String, PathBuf, fs::Metadata)>>
+{
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(dir)?
+    {
+        let entry = match entry
+        {
+            Ok(entry) => entry,
+            Err(_) =>
+            {
+                skipped.push(dir.to_path_buf());
+                continue;
+            },
+        };
+
+        match (entry.file_name().to_str(), fs::symlink_metadata(entry.path()))
+        {
+            (Some(name), Ok(metadata)) if !name.contains(char::is_whitespace) && (metadata.is_dir() || metadata.is_file()) =>
+            {
+                entries.push((String::from(name), entry.path(), metadata));
+            },
+            _ => skipped.push(entry.path()),
+        }
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    Ok(entries)
+}
+
+// Walks a real directory tree into a transcript fill_fs can read back: cd into every directory, ls it, visit its
+// subdirectories in name order, cd back out.  The transcript format splits on spaces and has no room for anything but
+// plain files and directories, so entries with whitespace or non UTF-8 in their names, symlinks and other special
+// files are left out and returned alongside the transcript instead, as is anything that cannot be read.  Only an
+// unreadable root fails the walk.
+pub fn transcript_from_dir(root: &Path) -> io::Result<(Vec<String>, Vec<PathBuf>)>
+{
+    let mut lines = vec![String::from("$ cd /")];
+    let mut skipped = Vec::new();
+    // Directories still to walk along with their listings, and None wherever the walk has to climb back out of one.
+    let root_listing = read_listing(root, &mut skipped)?;
+    let mut to_visit = vec![Some((None, root_listing))];
+
+    while let Some(next) = to_visit.pop()
+    {
+        let (name, entries) = match next
+        {
+            Some(dir) => dir,
+            None =>
+            {
+                lines.push(String::from("$ cd .."));
+                continue;
+            },
+        };
+
+        if let Some(name) = name
+        {
+            lines.push(format!("$ cd {}", name));
+            to_visit.push(None);
+        }
+
+        lines.push(String::from("$ ls"));
+        let mut subdirs = Vec::new();
+        for (name, path, metadata) in entries
+        {
+            if metadata.is_dir()
+            {
+                // Listed up front, so a directory that cannot be read never makes it into the transcript at all.
+                match read_listing(&path, &mut skipped)
+                {
+                    Ok(listing) =>
+                    {
+                        lines.push(format!("dir {}", name));
+                        subdirs.push(Some((Some(name), listing)));
+                    },
+                    Err(_) => skipped.push(path),
+                }
+            }
+            else
+            {
+                lines.push(format!("{} {}", metadata.len(), name));
+            }
+        }
+
+        to_visit.extend(subdirs.into_iter().rev());
+    }
+
+    Ok((lines, skipped))
+}
+
+// Recreates the filesystem under target, which must not exist yet, as real directories and files of the right sizes.
+// The files are only ever extended with set_len, so on any filesystem that supports sparse files they take up next to
+// no space however big they claim to be.
+pub fn materialize(filesystem: &Filesystem, target: &Path) -> io::Result<()>
+{
+    fs::create_dir(target)?;
+    let mut to_visit = vec![(ROOT, target.to_path_buf())];
+
+    while let Some((id, path)) = to_visit.pop()
+    {
+        let dir = filesystem.directory(id);
+
+        for file in dir.files()
+        {
+            fs::File::create(path.join(checked_name(&file.name)?))?.set_len(file.size as u64)?;
+        }
+
+        for child in dir.children()
+        {
+            let child_path = path.join(checked_name(filesystem.directory(*child).name())?);
+            fs::create_dir(&child_path)?;
+            to_visit.push((*child, child_path));
+        }
+    }
+
+    Ok(())
+}
+
+// Materializes into a fresh directory under the system temp directory and returns where that is.  Removing it again
+// is left to the caller.
+pub fn materialize_to_temp(filesystem: &Filesystem) -> io::Result<PathBuf>
+{
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|elapsed| elapsed.subsec_nanos()).unwrap_or(0);
+    let target = std::env::temp_dir().join(format!("advent_day_7_{}_{}", process::id(), nanos));
+
+    materialize(filesystem, &target)?;
+    Ok(target)
+}
+
+// A name from a transcript could be anything; never let one climb out of the target.
+fn checked_name(name: &str) -> io::Result<&str>
+{
+    if name.is_empty() || name == "." || name == ".." || name.contains('/') || name.contains('\\')
+    {
+        return Err(io::Error::new(ErrorKind::InvalidInput, format!("{} cannot be used as a file name", name)));
+    }
+
+    Ok(name)
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use std::fs;
+
+    use crate::day7::advent::{fill_fs_checked, Directory, Filesystem};
+    use crate::day7::advent::test::sample;
+    use crate::day7::report::{disk_usage, ReportOptions};
+
+    use super::{materialize, materialize_to_temp, transcript_from_dir};
+
+    #[test]
+    pub fn a_materialized_filesystem_walks_back_into_the_same_filesystem()
+    {
+        let (model, _) = fill_fs_checked(sample());
+        let target = materialize_to_temp(&model).unwrap();
+
+        assert_eq!(fs::metadata(target.join("d").join("k")).unwrap().len(), 7214296);
+
+        fs::write(target.join("has a space"), b"dropped").unwrap();
+        let walked = transcript_from_dir(&target);
+        fs::remove_dir_all(&target).unwrap();
+
+        let (lines, skipped) = walked.unwrap();
+        assert_eq!(skipped, vec![target.join("has a space")]);
+        assert_eq!(&lines[..4], &["$ cd /", "$ ls", "dir a", "14848514 b.txt"]);
+
+        let (round_trip, warnings) = fill_fs_checked(lines.iter().map(|line| line.as_str()).collect());
+        assert!(warnings.is_empty());
+        assert_eq!(disk_usage(&round_trip, &ReportOptions::default()), disk_usage(&model, &ReportOptions::default()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    pub fn a_dangling_symlink_is_skipped_rather_than_failing_the_walk()
+    {
+        let (model, _) = fill_fs_checked(sample());
+        let target = materialize_to_temp(&model).unwrap();
+        let dangling = target.join("d").join("gone");
+        std::os::unix::fs::symlink(target.join("nowhere"), &dangling).unwrap();
+
+        let walked = transcript_from_dir(&target);
+        fs::remove_dir_all(&target).unwrap();
+
+        let (lines, skipped) = walked.unwrap();
+        assert_eq!(skipped, vec![dangling]);
+        let (round_trip, warnings) = fill_fs_checked(lines.iter().map(|line| line.as_str()).collect());
+        assert!(warnings.is_empty());
+        assert_eq!(round_trip.directory(crate::day7::advent::ROOT).size(), 48381165);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[ignore = "permissions do not stop root reading a directory; run with --ignored as an ordinary user"]
+    pub fn an_unreadable_directory_is_skipped_rather_than_failing_the_walk()
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (model, _) = fill_fs_checked(sample());
+        let target = materialize_to_temp(&model).unwrap();
+        let locked = target.join("a");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+
+        let walked = transcript_from_dir(&target);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(&target).unwrap();
+
+        let (lines, skipped) = walked.unwrap();
+        assert_eq!(skipped, vec![locked]);
+        assert!(!lines.iter().any(|line| line == "dir a" || line == "$ cd a"));
+        let (round_trip, _) = fill_fs_checked(lines.iter().map(|line| line.as_str()).collect());
+        assert_eq!(round_trip.directory(crate::day7::advent::ROOT).size(), 48381165 - 94853);
+    }
+
+    #[test]
+    pub fn names_that_would_escape_the_target_are_refused()
+    {
+        let mut model = Filesystem::new();
+        model.create_dir(Directory::new(String::from("..")));
+
+        let target = std::env::temp_dir().join(format!("advent_day_7_escape_{}", std::process::id()));
+        let result = materialize(&model, &target);
+        fs::remove_dir_all(&target).unwrap();
+
+        assert!(result.is_err());
+    }
+}
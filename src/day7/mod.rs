@@ -1 +1,6 @@
-pub mod advent;
\ No newline at end of file
+pub mod advent;
+pub mod report;
+pub mod cleanup;
+pub mod synthetic;
+pub mod query;
+pub mod disk;
\ No newline at end of file
@@ -0,0 +1,382 @@
+use std::fmt;
+
+use super::advent::{DirId, Filesystem, PathError};
+use super::report::SortOrder;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Kind
+{
+    File,
+    Dir,
+}
+
+// A number to compare against, written the way find writes them: +n is more than n, -n less than n, plain n exactly
+// n.  <n and >n are accepted too.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Bound
+{
+    Less(usize),
+    Exactly(usize),
+    More(usize),
+}
+
+impl Bound
+{
+    pub fn admits(&self, value: usize) -> bool
+    {
+        match self
+        {
+            Bound::Less(limit) => value < *limit,
+            Bound::Exactly(limit) => value == *limit,
+            Bound::More(limit) => value > *limit,
+        }
+    }
+}
+
+// A find-like search over a Filesystem.  Every test left as None lets everything through.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Query
+{
+    // Where the search starts, absolute or relative to the cwd.
+    pub start: String,
+    pub kind: Option<Kind>,
+    // Glob over the last path segment: * for any run of characters, ? for any one.
+    pub name: Option<String>,
+    pub size: Option<Bound>,
+    // Levels below the start, which is itself depth 0.
+    pub depth: Option<Bound>,
+    // Name sorts by full path.
+    pub sort: SortOrder,
+}
+
+impl Default for Query
+{
+    fn default() -> Query
+    {
+        Query { start: String::from("/"), kind: None, name: None, size: None, depth: None, sort: SortOrder::Name }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Found
+{
+    pub path: String,
+    pub kind: Kind,
+    pub size: usize,
+    pub depth: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum QueryError
+{
+    UnterminatedQuote,
+    UnknownOption(String),
+    MissingValue(String),
+    BadValue { option: String, value: String },
+    // Something other than an option after the starting directory.
+    UnexpectedArgument(String),
+    Start(PathError),
+}
+
+impl fmt::Display for QueryError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            QueryError::UnterminatedQuote => write!(f, "a quote is never closed"),
+            QueryError::UnknownOption(option) => write!(f, "unknown option {}", option),
+            QueryError::MissingValue(option) => write!(f, "{} needs a value", option),
+            QueryError::BadValue { option, value } => write!(f, "{} cannot be {}", option, value),
+            QueryError::UnexpectedArgument(argument) => write!(f, "{} is not an option, and there is only one starting point", argument),
+            QueryError::Start(err) => write!(f, "the starting point is no good: {}", err),
+        }
+    }
+}
+
+// Splits on whitespace, keeping anything in single or double quotes together with the quotes removed.
+fn tokenize(query: &str) -> Result<Vec<String>, QueryError>
+{
+    let mut tokens = Vec::new();
+    let mut current: Option<String> = None;
+    let mut quote: Option<char> = None;
+
+    for c in query.chars()
+    {
+        match quote
+        {
+            Some(open) if c == open => quote = None,
+            Some(_) => current.get_or_insert_with(String::new).push(c),
+            None if c == '\'' || c == '"' =>
+            {
+                quote = Some(c);
+                current.get_or_insert_with(String::new);
+            },
+            None if c.is_whitespace() => tokens.extend(current.take()),
+            None => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some()
+    {
+        return Err(QueryError::UnterminatedQuote);
+    }
+    tokens.extend(current);
+
+    Ok(tokens)
+}
+
+// k, M and G suffixes count in 1024s, as -size does.
+fn parse_bound(option: &str, value: &str, with_units: bool) -> Result<Bound, QueryError>
+{
+    let bad_value = || QueryError::BadValue { option: String::from(option), value: String::from(value) };
+
+    let (make, number): (fn(usize) -> Bound, &str) = match value.chars().next()
+    {
+        Some('+') | Some('>') => (Bound::More, &value[1..]),
+        Some('-') | Some('<') => (Bound::Less, &value[1..]),
+        _ => (Bound::Exactly, value),
+    };
+
+    let (digits, scale) = match number.chars().last()
+    {
+        Some('k') if with_units => (&number[..number.len() - 1], 1024),
+        Some('M') if with_units => (&number[..number.len() - 1], 1024 * 1024),
+        Some('G') if with_units => (&number[..number.len() - 1], 1024 * 1024 * 1024),
+        _ => (number, 1),
+    };
+
+    match digits.parse::<usize>()
+    {
+        Ok(amount) => amount.checked_mul(scale).map(make).ok_or_else(bad_value),
+        Err(_) => Err(bad_value()),
+    }
+}
+
+// Reads e.g. find / -type f -name '*.dat' -size +100k -depth <3 -sort size.  The leading find and the starting
+// directory are both optional, the start defaulting to the root.
+pub fn parse_query(query: &str) -> Result<Query, QueryError>
+{
+    let tokens = tokenize(query)?;
+    parse_query_tokens(&tokens)
+}
+
+// The same query already split into arguments, e.g. by the shell, so no quotes are looked for.
+pub fn parse_query_tokens<S: AsRef<str>>(tokens: &[S]) -> Result<Query, QueryError>
+{
+    let mut parsed = Query::default();
+    let mut tokens = tokens.iter().map(|token| token.as_ref()).peekable();
+
+    tokens.next_if_eq(&"find");
+    if let Some(start) = tokens.next_if(|token| !token.starts_with('-'))
+    {
+        parsed.start = String::from(start);
+    }
+
+    while let Some(option) = tokens.next()
+    {
+        if !option.starts_with('-')
+        {
+            return Err(QueryError::UnexpectedArgument(String::from(option)));
+        }
+
+        let value = match tokens.next()
+        {
+            Some(value) => value,
+            None if ["-type", "-name", "-size", "-depth", "-sort"].contains(&option) => return Err(QueryError::MissingValue(String::from(option))),
+            None => return Err(QueryError::UnknownOption(String::from(option))),
+        };
+
+        match option
+        {
+            "-type" =>
+            {
+                parsed.kind = match value
+                {
+                    "f" => Some(Kind::File),
+                    "d" => Some(Kind::Dir),
+                    _ => return Err(QueryError::BadValue { option: String::from(option), value: String::from(value) }),
+                };
+            },
+            "-name" => parsed.name = Some(String::from(value)),
+            "-size" => parsed.size = Some(parse_bound(option, value, true)?),
+            "-depth" => parsed.depth = Some(parse_bound(option, value, false)?),
+            "-sort" =>
+            {
+                parsed.sort = match value
+                {
+                    "path" | "name" => SortOrder::Name,
+                    "size" => SortOrder::Size,
+                    _ => return Err(QueryError::BadValue { option: String::from(option), value: String::from(value) }),
+                };
+            },
+            _ => return Err(QueryError::UnknownOption(String::from(option))),
+        }
+    }
+
+    Ok(parsed)
+}
+
+pub fn glob_matches(pattern: &str, name: &str) -> bool
+{
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    // On a mismatch, let the most recent * swallow one more character and carry on from there.
+    let (mut p, mut n) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+
+    while n < name.len()
+    {
+        if p < pattern.len() && pattern[p] == '*'
+        {
+            last_star = Some((p, n));
+            p += 1;
+        }
+        else if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n])
+        {
+            p += 1;
+            n += 1;
+        }
+        else if let Some((star, swallowed)) = last_star
+        {
+            last_star = Some((star, swallowed + 1));
+            p = star + 1;
+            n = swallowed + 1;
+        }
+        else
+        {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+impl Query
+{
+    fn admits(&self, name: &str, kind: Kind, size: usize, depth: usize) -> bool
+    {
+        self.kind.is_none_or(|wanted| wanted == kind)
+            && self.name.as_ref().is_none_or(|pattern| glob_matches(pattern, name))
+            && self.size.is_none_or(|bound| bound.admits(size))
+            && self.depth.is_none_or(|bound| bound.admits(depth))
+    }
+
+    // No point walking below the deepest level the depth test can let through.
+    fn deepest(&self) -> Option<usize>
+    {
+        match self.depth
+        {
+            Some(Bound::Less(limit)) => Some(limit.saturating_sub(1)),
+            Some(Bound::Exactly(limit)) => Some(limit),
+            _ => None,
+        }
+    }
+}
+
+pub fn run_query(fs: &Filesystem, query: &Query) -> Result<Vec<Found>, QueryError>
+{
+    let start = fs.resolve(&query.start).map_err(QueryError::Start)?;
+    let deepest = query.deepest();
+
+    let mut found = Vec::new();
+    let mut to_visit: Vec<(DirId, usize)> = vec![(start, 0)];
+
+    while let Some((id, depth)) = to_visit.pop()
+    {
+        let dir = fs.directory(id);
+        if query.admits(dir.name(), Kind::Dir, dir.size(), depth)
+        {
+            found.push(Found { path: fs.path_of(id), kind: Kind::Dir, size: dir.size(), depth });
+        }
+
+        if deepest.is_some_and(|deepest| depth >= deepest)
+        {
+            continue;
+        }
+
+        for file in dir.files()
+        {
+            if query.admits(&file.name, Kind::File, file.size, depth + 1)
+            {
+                found.push(Found { path: fs.child_path(id, &file.name), kind: Kind::File, size: file.size, depth: depth + 1 });
+            }
+        }
+        to_visit.extend(dir.children().iter().map(|child| (*child, depth + 1)));
+    }
+
+    match query.sort
+    {
+        SortOrder::Name => found.sort_by(|a, b| a.path.cmp(&b.path)),
+        SortOrder::Size => found.sort_by(|a, b| b.size.cmp(&a.size).then(a.path.cmp(&b.path))),
+    }
+
+    Ok(found)
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day7::advent::fill_fs_checked;
+    use crate::day7::advent::test::sample;
+    use crate::day7::report::SortOrder;
+
+    use super::{glob_matches, parse_query, parse_query_tokens, run_query, Bound, Kind, Query, QueryError};
+
+    fn paths(query: &str) -> Vec<String>
+    {
+        let (fs, _) = fill_fs_checked(sample());
+        run_query(&fs, &parse_query(query).unwrap()).unwrap().into_iter().map(|found| found.path).collect()
+    }
+
+    #[test]
+    pub fn a_full_query_parses_into_every_test()
+    {
+        let query = parse_query("find /a -type f -name '*.dat' -size +100k -depth <3 -sort size").unwrap();
+
+        assert_eq!(query, Query {
+            start: String::from("/a"),
+            kind: Some(Kind::File),
+            name: Some(String::from("*.dat")),
+            size: Some(Bound::More(102400)),
+            depth: Some(Bound::Less(3)),
+            sort: SortOrder::Size,
+        });
+        assert_eq!(parse_query("-type x"), Err(QueryError::BadValue { option: String::from("-type"), value: String::from("x") }));
+        assert_eq!(parse_query("/ -name 'open"), Err(QueryError::UnterminatedQuote));
+        assert_eq!(parse_query("/ -size"), Err(QueryError::MissingValue(String::from("-size"))));
+        assert_eq!(parse_query("/ /a"), Err(QueryError::UnexpectedArgument(String::from("/a"))));
+        assert_eq!(parse_query_tokens(&["/", "-name", "a b*"]).unwrap().name, Some(String::from("a b*")));
+    }
+
+    #[test]
+    pub fn globs_match_whole_names()
+    {
+        assert!(glob_matches("*.dat", "c.dat"));
+        assert!(glob_matches("d.*", "d.log"));
+        assert!(glob_matches("?", "k"));
+        assert!(glob_matches("*a*t*", "c.dat"));
+        assert!(glob_matches("*b", "*ab"));
+        assert!(!glob_matches("*.dat", "c.dat.old"));
+        assert!(!glob_matches("?", "de"));
+    }
+
+    #[test]
+    pub fn queries_find_files_and_directories_by_full_path()
+    {
+        assert_eq!(paths("find / -type f -name 'd.*'"), vec!["/d/d.ext", "/d/d.log"]);
+        assert_eq!(paths("find / -type d"), vec!["/", "/a", "/a/e", "/d"]);
+        assert_eq!(paths("/a -depth 1"), vec!["/a/e", "/a/f", "/a/g", "/a/h.lst"]);
+        assert_eq!(paths("/ -size +8M -sort size"), vec!["/", "/d", "/b.txt", "/c.dat"]);
+        assert_eq!(paths("/ -type f -depth <2 -size -5M"), Vec::<String>::new());
+    }
+
+    #[test]
+    pub fn a_missing_start_is_an_error()
+    {
+        let (fs, _) = fill_fs_checked(sample());
+
+        assert!(matches!(run_query(&fs, &parse_query("/nope").unwrap()), Err(QueryError::Start(_))));
+    }
+}
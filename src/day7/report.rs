@@ -0,0 +1,225 @@
+use super::advent::{DirId, Filesystem, ROOT};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SortOrder
+{
+    // Alphabetically by name, or by full path for du.
+    Name,
+    // Largest first, ties broken by name.
+    Size,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ReportOptions
+{
+    // Deepest level shown, the root being depth 0.  None shows everything.
+    pub max_depth: Option<usize>,
+    pub sort: SortOrder,
+    // Sizes as 1.5K, 93K, 47M rather than plain byte counts.
+    pub human: bool,
+}
+
+impl Default for ReportOptions
+{
+    fn default() -> ReportOptions
+    {
+        ReportOptions { max_depth: None, sort: SortOrder::Name, human: false }
+    }
+}
+
+// Rounds up, to one decimal place below 10 units and to whole units above, the way du -h does, so a size is never
+// shown smaller than it is.  Anything under 1K is a plain byte count.
+pub fn human_size(bytes: usize) -> String
+{
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+
+    if bytes < 1024
+    {
+        return bytes.to_string();
+    }
+
+    let bytes = bytes as u128;
+    let mut unit = 0;
+    let mut divisor: u128 = 1024;
+    // Rounding up can carry a size into the next unit, 1023.5K becoming 1.0M.
+    while bytes.div_ceil(divisor) >= 1024 && unit < UNITS.len() - 1
+    {
+        divisor *= 1024;
+        unit += 1;
+    }
+
+    let tenths = (bytes * 10).div_ceil(divisor);
+    if tenths < 100
+    {
+        format!("{}.{}{}", tenths / 10, tenths % 10, UNITS[unit])
+    }
+    else
+    {
+        format!("{}{}", bytes.div_ceil(divisor), UNITS[unit])
+    }
+}
+
+fn format_size(bytes: usize, options: &ReportOptions) -> String
+{
+    if options.human { human_size(bytes) } else { bytes.to_string() }
+}
+
+enum Entry
+{
+    Dir(DirId),
+    File,
+}
+
+// Directories and files directly inside id, sorted as asked, each with its name and size.
+fn entries(fs: &Filesystem, id: DirId, sort: SortOrder) -> Vec<(String, usize, Entry)>
+{
+    let dir = fs.directory(id);
+
+    let mut entries: Vec<(String, usize, Entry)> = dir.children().iter().map(|child|
+    {
+        let child_dir = fs.directory(*child);
+        (child_dir.name().to_string(), child_dir.size(), Entry::Dir(*child))
+    }).collect();
+    entries.extend(dir.files().iter().map(|file| (file.name.clone(), file.size, Entry::File)));
+
+    match sort
+    {
+        SortOrder::Name => entries.sort_by(|a, b| a.0.cmp(&b.0)),
+        SortOrder::Size => entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0))),
+    }
+
+    entries
+}
+
+// Draws the filesystem the way tree does, directories marked with a trailing / and every entry followed by its size.
+pub fn render_tree(fs: &Filesystem, options: &ReportOptions) -> Vec<String>
+{
+    let mut lines = vec![format!("/ ({})", format_size(fs.directory(ROOT).size(), options))];
+
+    render_tree_level(fs, ROOT, "", 1, options, &mut lines);
+
+    lines
+}
+
+fn render_tree_level(fs: &Filesystem, id: DirId, prefix: &str, depth: usize, options: &ReportOptions, lines: &mut Vec<String>)
+{
+    if options.max_depth.is_some_and(|max_depth| depth > max_depth)
+    {
+        return;
+    }
+
+    let entries = entries(fs, id, options.sort);
+    let last = entries.len().saturating_sub(1);
+
+    for (position, (name, size, entry)) in entries.into_iter().enumerate()
+    {
+        let (branch, indent) = if position == last { ("└── ", "    ") } else { ("├── ", "│   ") };
+
+        match entry
+        {
+            Entry::Dir(child) =>
+            {
+                lines.push(format!("{}{}{}/ ({})", prefix, branch, name, format_size(size, options)));
+                render_tree_level(fs, child, &format!("{}{}", prefix, indent), depth + 1, options, lines);
+            },
+            Entry::File => lines.push(format!("{}{}{} ({})", prefix, branch, name, format_size(size, options))),
+        }
+    }
+}
+
+// Every directory down to the depth limit as (absolute path, total size), the same set du would print.
+pub fn disk_usage(fs: &Filesystem, options: &ReportOptions) -> Vec<(String, usize)>
+{
+    let mut usage = Vec::new();
+    let mut to_visit = vec![(ROOT, 0)];
+
+    while let Some((id, depth)) = to_visit.pop()
+    {
+        let dir = fs.directory(id);
+        usage.push((fs.path_of(id), dir.size()));
+
+        if options.max_depth.is_none_or(|max_depth| depth < max_depth)
+        {
+            to_visit.extend(dir.children().iter().map(|child| (*child, depth + 1)));
+        }
+    }
+
+    match options.sort
+    {
+        SortOrder::Name => usage.sort_by(|a, b| a.0.cmp(&b.0)),
+        SortOrder::Size => usage.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0))),
+    }
+
+    usage
+}
+
+pub fn render_du(fs: &Filesystem, options: &ReportOptions) -> Vec<String>
+{
+    disk_usage(fs, options).into_iter().map(|(path, size)| format!("{}\t{}", format_size(size, options), path)).collect()
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day7::advent::fill_fs_checked;
+    use crate::day7::advent::test::sample;
+
+    use super::{disk_usage, human_size, render_du, render_tree, ReportOptions, SortOrder};
+
+    #[test]
+    pub fn tree_draws_every_entry_with_its_size()
+    {
+        let (fs, _) = fill_fs_checked(sample());
+
+        let tree = render_tree(&fs, &ReportOptions::default());
+
+        assert_eq!(tree[0], "/ (48381165)");
+        assert_eq!(tree[1], "├── a/ (94853)");
+        assert_eq!(tree[2], "│   ├── e/ (584)");
+        assert_eq!(tree[3], "│   │   └── i (584)");
+        assert_eq!(tree[9], "└── d/ (24933642)");
+        assert_eq!(tree.last().unwrap(), "    └── k (7214296)");
+        assert_eq!(tree.len(), 14);
+    }
+
+    #[test]
+    pub fn tree_respects_the_depth_limit_and_size_order()
+    {
+        let (fs, _) = fill_fs_checked(sample());
+        let options = ReportOptions { max_depth: Some(1), sort: SortOrder::Size, human: true };
+
+        let tree = render_tree(&fs, &options);
+
+        assert_eq!(tree, vec!["/ (47M)", "├── d/ (24M)", "├── b.txt (15M)", "├── c.dat (8.2M)", "└── a/ (93K)"]);
+    }
+
+    #[test]
+    pub fn du_lists_every_directory_by_absolute_path()
+    {
+        let (fs, _) = fill_fs_checked(sample());
+
+        assert_eq!(disk_usage(&fs, &ReportOptions::default()), vec![
+            (String::from("/"), 48381165),
+            (String::from("/a"), 94853),
+            (String::from("/a/e"), 584),
+            (String::from("/d"), 24933642),
+        ]);
+
+        let options = ReportOptions { max_depth: Some(1), sort: SortOrder::Size, human: false };
+        assert_eq!(render_du(&fs, &options), vec!["48381165\t/", "24933642\t/d", "94853\t/a"]);
+    }
+
+    #[test]
+    pub fn human_sizes_use_binary_units()
+    {
+        assert_eq!(human_size(584), "584");
+        assert_eq!(human_size(1024), "1.0K");
+        assert_eq!(human_size(1025), "1.1K");
+        assert_eq!(human_size(1536), "1.5K");
+        assert_eq!(human_size(94853), "93K");
+        assert_eq!(human_size(8504156), "8.2M");
+        assert_eq!(human_size(10 * 1024 - 1), "10K");
+        assert_eq!(human_size(1024 * 1024 - 1), "1.0M");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0G");
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::{Duration, Instant};
+
+use super::advent::{fill_fs_checked, ROOT};
+use super::report::{disk_usage, ReportOptions};
+
+// A random transcript of dirs directories below the root, each holding up to three files.  Every directory hangs off
+// one chosen at random from those made before it, which gives a bushy tree a few dozen levels deep at most.  The
+// transcript is a single depth first walk: cd into a directory, ls it, visit its children, cd back out.
+pub fn synthetic_transcript(dirs: usize, seed: u64) -> Vec<String>
+{
+    let mut state = seed;
+    let mut next_random = move |bound: usize|
+    {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        ((state >> 33) % bound as u64) as usize
+    };
+
+    let mut children: Vec<Vec<usize>> = vec![Vec::new(); dirs + 1];
+    for dir in 1..=dirs
+    {
+        children[next_random(dir)].push(dir);
+    }
+    let files: Vec<Vec<usize>> = (0..=dirs).map(|_| (0..next_random(4)).map(|_| 1 + next_random(250000)).collect()).collect();
+
+    let mut lines = vec![String::from("$ cd /")];
+    // Directories still to walk, and None wherever the walk has to climb back out of one.
+    let mut to_visit = vec![Some(0)];
+
+    while let Some(next) = to_visit.pop()
+    {
+        let dir = match next
+        {
+            Some(dir) => dir,
+            None =>
+            {
+                lines.push(String::from("$ cd .."));
+                continue;
+            },
+        };
+
+        if dir != 0
+        {
+            lines.push(format!("$ cd d{}", dir));
+            to_visit.push(None);
+        }
+
+        lines.push(String::from("$ ls"));
+        lines.extend(children[dir].iter().map(|child| format!("dir d{}", child)));
+        lines.extend(files[dir].iter().enumerate().map(|(number, size)| format!("{} f{}.dat", size, number)));
+
+        to_visit.extend(children[dir].iter().rev().map(|child| Some(*child)));
+    }
+
+    lines
+}
+
+// Times building a filesystem from a synthetic transcript and the passes over it that everything else relies on.
+pub fn benchmark(dirs: usize) -> Vec<(&'static str, Duration)>
+{
+    let mut timings = Vec::new();
+
+    let started = Instant::now();
+    let transcript = synthetic_transcript(dirs, 2022);
+    timings.push(("generate transcript", started.elapsed()));
+
+    let started = Instant::now();
+    let (mut fs, _) = fill_fs_checked(transcript.iter().map(|line| line.as_str()).collect());
+    timings.push(("replay transcript", started.elapsed()));
+
+    let started = Instant::now();
+    fs.update_sizes();
+    timings.push(("recompute sizes", started.elapsed()));
+
+    let started = Instant::now();
+    let usage = disk_usage(&fs, &ReportOptions::default());
+    timings.push(("du every directory", started.elapsed()));
+
+    let started = Instant::now();
+    let total = fs.directory(ROOT).size();
+    let small: usize = fs.breadth_first_filter_size(100000).iter().map(|(_, size)| size).sum();
+    timings.push(("puzzle queries", started.elapsed()));
+
+    println!("{} directories, {} transcript lines, {} bytes in total, {} in small directories", usage.len() - 1, transcript.len(), total, small);
+    timings
+}
+
+#[cfg(test)]
+pub mod tests
+{
+    use crate::day7::advent::{fill_fs_checked, ROOT};
+    use crate::day7::report::{disk_usage, ReportOptions};
+
+    use super::synthetic_transcript;
+
+    #[test]
+    pub fn a_synthetic_transcript_replays_into_exactly_that_many_directories()
+    {
+        let transcript = synthetic_transcript(500, 7);
+        let file_bytes: usize = transcript.iter().filter_map(|line| line.split(' ').next().unwrap().parse::<usize>().ok()).sum();
+
+        let (mut fs, warnings) = fill_fs_checked(transcript.iter().map(|line| line.as_str()).collect());
+
+        assert!(warnings.is_empty());
+        assert_eq!(fs.breadth_first_filter(|_| true).len(), 501);
+        assert_eq!(fs.directory(ROOT).size(), file_bytes);
+
+        // Sizes kept up as the transcript was read must match a full recount.
+        let incremental = disk_usage(&fs, &ReportOptions::default());
+        fs.update_sizes();
+        assert_eq!(disk_usage(&fs, &ReportOptions::default()), incremental);
+    }
+}
@@ -22,10 +22,28 @@ use day12::advent::solver_day12;
 use day13::advent::{solve_day_13, solve_day_13_2};
 use day14::advent::{solve_day_14_1, solve_day_14_2};
 use day2::advent::evaluate_tournament;
+use day2::mixed::{print_solution, solve_guide};
+use day2::tournament::{Copycat, FrequencyCounter, GuideReplay, RandomSeeded, Strategy, print_tournament, round_robin};
 use day3::advent::analyze_rucksacks;
+use day3::repack::{plan_repacking, print_repacking};
+use day3::report::{build_report, render_report, OutputFormat};
 use day4::advent::count_contained_pairs;
-use day6::advent::scan_datastream;
-use day7::advent::{space_finder};
+use day4::coverage::print_coverage;
+use day4::population::{collect_assignments, print_population};
+use day5::advent::solve_with_crane;
+use day5::crane::crane_by_model;
+use day5::planner::{plan, program, PlanError, Target};
+use day5::replay::Replay;
+use day5::validate::{describe, execute_lenient, validate_program, Leniency};
+use day6::advent::find_markers;
+use day6::search::{benchmark, benchmark_stream};
+use day6::stream::MarkerScanner;
+use day7::advent::{fill_fs_checked, space_finder, ROOT};
+use day7::cleanup::{plan_cleanup, DiskSpec, Objective};
+use day7::report::{render_du, render_tree, ReportOptions, SortOrder};
+use day7::synthetic;
+use day7::query::{parse_query_tokens, run_query, Kind};
+use day7::disk::{materialize, materialize_to_temp, transcript_from_dir};
 use day8::advent::part1;
 use day9::advent::solve_day_9;
 use lib::lib::to_untrimmed_lines;
@@ -38,10 +56,23 @@ use crate::lib::lib::to_lines;
 fn main() 
 {
     env_logger::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty()
+    {
+        run_command(&args);
+        return;
+    }
+
     // advent_day_1();
     // advent_day_2();
+    // advent_day_2_tournament();
+    // advent_day_2_mixed_strategy();
     // advent_day_3()
+    // advent_day_3_repack();
     // advent_day_4();
+    // advent_day_4_population();
+    // advent_day_4_coverage();
     // advent_day_5();
     // advent_day_6();
     // advent_day_7();
@@ -53,6 +84,155 @@ fn main()
     advent_day_14()
 }
 
+fn run_command(args: &[String])
+{
+    match args[0].as_str()
+    {
+        "day3-report" => 
+        {
+            let format = match args.get(1).map(|arg| arg.as_str())
+            {
+                Some("json") => OutputFormat::Json,
+                _ => OutputFormat::Text,
+            };
+            let path = args.get(2).map(|arg| arg.as_str()).unwrap_or("./advent_day_3_1_real");
+            advent_day_3_report(path, format);
+        },
+        "day5" => 
+        {
+            let model = args.get(1).map(|arg| arg.as_str()).unwrap_or("both");
+            let path = args.get(2).map(|arg| arg.as_str()).unwrap_or("./advent_day_5_1_real");
+            advent_day_5_with_crane(path, model);
+        },
+        "day5-validate" => 
+        {
+            let path = args.get(1).map(|arg| arg.as_str()).unwrap_or("./advent_day_5_1_real");
+            advent_day_5_validate(path);
+        },
+        "day5-lenient" => 
+        {
+            let model = args.get(1).map(|arg| arg.as_str()).unwrap_or("9001");
+            let leniency = match args.get(2).map(|arg| arg.as_str())
+            {
+                Some("clamp") => Leniency::Clamp,
+                _ => Leniency::Skip,
+            };
+            let path = args.get(3).map(|arg| arg.as_str()).unwrap_or("./advent_day_5_1_real");
+            advent_day_5_lenient(path, model, leniency);
+        },
+        "day5-replay" => 
+        {
+            let model = args.get(1).map(|arg| arg.as_str()).unwrap_or("9001");
+            let path = args.get(2).map(|arg| arg.as_str()).unwrap_or("./advent_day_5_1_real");
+            advent_day_5_replay(path, model, args.get(3).map(|arg| arg.as_str()));
+        },
+        "day5-plan" => 
+        {
+            if args.len() < 4
+            {
+                println!("day5-plan needs a crane model, an input and a target.");
+                return;
+            }
+            advent_day_5_plan(&args[2], &args[1], &args[3]);
+        },
+        "day6-stream" => 
+        {
+            let frames = args.get(1).map(|arg| arg.as_str()) == Some("frames");
+            let window = args.get(2).map(|arg| arg.parse::<usize>().expect("The window should be a number.")).unwrap_or(4);
+            let path = args.get(3).map(|arg| arg.as_str()).unwrap_or("./advent_day_6_1_real");
+            advent_day_6_stream(path, window, frames);
+        },
+        "day6-bench" => 
+        {
+            let window = args.get(1).map(|arg| arg.parse::<usize>().expect("The window should be a number.")).unwrap_or(14);
+            let megabytes = args.get(2).map(|arg| arg.parse::<usize>().expect("The size should be a number.")).unwrap_or(64);
+            advent_day_6_bench(window, megabytes);
+        },
+        "day7-report" => 
+        {
+            let tree = args.get(1).map(|arg| arg.as_str()) == Some("tree");
+            let path = args.get(2).map(|arg| arg.as_str()).unwrap_or("./advent_day_7_1_real");
+            advent_day_7_report(path, tree, report_options(&args[args.len().min(3)..]));
+        },
+        "day7-cleanup" => 
+        {
+            let objective = match args.get(1).map(|arg| arg.as_str())
+            {
+                Some("count") => Objective::MinCount,
+                _ => Objective::MinTotal,
+            };
+            let path = args.get(2).map(|arg| arg.as_str()).unwrap_or("./advent_day_7_1_real");
+            let mut spec = DiskSpec::default();
+            if let Some(total) = args.get(3)
+            {
+                spec.total = total.parse::<usize>().expect("The disk size should be a number.");
+            }
+            if let Some(needed) = args.get(4)
+            {
+                spec.needed = needed.parse::<usize>().expect("The space needed should be a number.");
+            }
+            advent_day_7_cleanup(path, &spec, objective);
+        },
+        "day7-find" => 
+        {
+            let path = args.get(1).map(|arg| arg.as_str()).unwrap_or("./advent_day_7_1_real");
+            advent_day_7_find(path, &args[args.len().min(2)..]);
+        },
+        "day7-walk" => 
+        {
+            let dir = args.get(1).map(|arg| arg.as_str()).unwrap_or(".");
+            advent_day_7_walk(dir, args.get(2).map(|arg| arg.as_str()));
+        },
+        "day7-materialize" => 
+        {
+            let path = args.get(1).map(|arg| arg.as_str()).unwrap_or("./advent_day_7_1_real");
+            advent_day_7_materialize(path, args.get(2).map(|arg| arg.as_str()));
+        },
+        "day7-bench" => 
+        {
+            let dirs = args.get(1).map(|arg| arg.parse::<usize>().expect("The directory count should be a number.")).unwrap_or(100000);
+            advent_day_7_bench(dirs);
+        },
+        _ => 
+        {
+            println!("Unknown command {}.  Available commands:", args[0]);
+            println!("  day3-report [text|json] [input]");
+            println!("  day5 [9000|9001|both] [input]");
+            println!("  day5-validate [input]");
+            println!("  day5-lenient [9000|9001] [skip|clamp] [input]");
+            println!("  day5-replay [9000|9001] [input] [export file]");
+            println!("  day5-plan <9000|9001> <input> <target drawing file|tops=XYZ>");
+            println!("  day6-stream [markers|frames] [window] [input]");
+            println!("  day6-bench [window] [megabytes]");
+            println!("  day7-report [tree|du] [input] [--depth N] [--sort size] [-h]");
+            println!("  day7-cleanup [total|count] [input] [disk size] [space needed]");
+            println!("  day7-find [input] [find / -type f|d -name 'GLOB' -size +100k -depth '<3' -sort size]");
+            println!("  day7-walk [directory] [transcript file]");
+            println!("  day7-materialize [input] [target directory]");
+            println!("  day7-bench [directories]");
+        }
+    }
+}
+
+fn report_options(flags: &[String]) -> ReportOptions
+{
+    let mut options = ReportOptions::default();
+    let mut flags = flags.iter();
+
+    while let Some(flag) = flags.next()
+    {
+        match flag.as_str()
+        {
+            "--depth" => options.max_depth = flags.next().map(|depth| depth.parse::<usize>().expect("The depth should be a number.")),
+            "--sort" => options.sort = if flags.next().map(|sort| sort.as_str()) == Some("size") { SortOrder::Size } else { SortOrder::Name },
+            "-h" => options.human = true,
+            _ => println!("Ignoring unknown option {}", flag),
+        }
+    }
+
+    options
+}
+
 pub fn advent_day_14()
 {
     let input_data = read_file_to_str("./advent_day_14_real");
@@ -111,6 +291,108 @@ pub fn advent_day_8()
     part1(lines)
 }
 
+pub fn advent_day_7_report(path: &str, tree: bool, options: ReportOptions)
+{
+    let input_data = read_file_to_str(path);
+    let (fs, warnings) = fill_fs_checked(to_lines(&input_data));
+    for warning in warnings
+    {
+        println!("Warning: {}", warning);
+    }
+
+    let lines = if tree { render_tree(&fs, &options) } else { render_du(&fs, &options) };
+    for line in lines
+    {
+        println!("{}", line);
+    }
+}
+
+pub fn advent_day_7_cleanup(path: &str, spec: &DiskSpec, objective: Objective)
+{
+    let input_data = read_file_to_str(path);
+    let (fs, _) = fill_fs_checked(to_lines(&input_data));
+
+    match plan_cleanup(&fs, spec, objective)
+    {
+        Some(plan) => 
+        {
+            for (dir_path, size) in &plan.delete
+            {
+                println!("rm -r {}  # {}", dir_path, size);
+            }
+            for line in &plan.explanation
+            {
+                println!("{}", line);
+            }
+        },
+        None => println!("Even deleting every directory would not free {} on a {} disk.", spec.needed, spec.total),
+    }
+}
+
+pub fn advent_day_7_find(path: &str, query: &[String])
+{
+    let input_data = read_file_to_str(path);
+    let (fs, _) = fill_fs_checked(to_lines(&input_data));
+
+    let found = match parse_query_tokens(query).and_then(|query| run_query(&fs, &query))
+    {
+        Ok(found) => found,
+        Err(err) => panic!("find {}: {}", query.join(" "), err),
+    };
+
+    for entry in found
+    {
+        let kind = if entry.kind == Kind::Dir { 'd' } else { 'f' };
+        println!("{}\t{}\t{}", kind, entry.size, entry.path);
+    }
+}
+
+pub fn advent_day_7_walk(dir: &str, output: Option<&str>)
+{
+    let (lines, skipped) = match transcript_from_dir(std::path::Path::new(dir))
+    {
+        Ok(walked) => walked,
+        Err(err) => panic!("Walking {} fell over: {}", dir, err),
+    };
+
+    for path in skipped
+    {
+        eprintln!("Skipped {}, the transcript has no way to show it.", path.display());
+    }
+
+    match output
+    {
+        Some(output_path) => std::fs::write(output_path, lines.join("\n") + "\n").expect("The transcript could not be written."),
+        None => lines.iter().for_each(|line| println!("{}", line)),
+    }
+}
+
+pub fn advent_day_7_materialize(path: &str, target: Option<&str>)
+{
+    let input_data = read_file_to_str(path);
+    let (fs, _) = fill_fs_checked(to_lines(&input_data));
+
+    let result = match target
+    {
+        Some(target_path) => materialize(&fs, std::path::Path::new(target_path)).map(|_| std::path::PathBuf::from(target_path)),
+        None => materialize_to_temp(&fs),
+    };
+
+    match result
+    {
+        Ok(created) => println!("Materialized {} bytes under {}", fs.directory(ROOT).size(), created.display()),
+        Err(err) => panic!("Materializing {} fell over: {}", path, err),
+    }
+}
+
+pub fn advent_day_7_bench(dirs: usize)
+{
+    for (stage, elapsed) in synthetic::benchmark(dirs)
+    {
+        println!("  {:<20} {:>10.2?}", stage, elapsed);
+    }
+}
+
 pub fn advent_day_7()
 {
     let input_data = read_file_to_str("./advent_day_7_1_real");
@@ -120,12 +402,51 @@ pub fn advent_day_7()
     space_finder(lines);
 }
 
+pub fn advent_day_6_stream(path: &str, window: usize, frames: bool)
+{
+    let file = match std::fs::File::open(path)
+    {
+        Ok(file) => file,
+        Err(_) => panic!("There is no file here named {}, you goon.", path),
+    };
+    let scanner = MarkerScanner::new(file, window);
+
+    if frames
+    {
+        for packet in scanner.frames()
+        {
+            let packet = packet.expect("Reading the datastream failed.");
+            println!("marker at {}, packet {}..{} ({} bytes)", packet.marker, packet.start, packet.end, packet.end - packet.start);
+        }
+    }
+    else
+    {
+        for marker in scanner
+        {
+            println!("{}", marker.expect("Reading the datastream failed."));
+        }
+    }
+}
+
+pub fn advent_day_6_bench(window: usize, megabytes: usize)
+{
+    let stream = benchmark_stream(megabytes * 1024 * 1024, window, 2022);
+    println!("Searching {} MiB for a {} byte marker, best of 5 rounds:", megabytes, window);
+
+    for (search, elapsed, found) in benchmark(&stream, window, 5)
+    {
+        let throughput = stream.len() as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64();
+        println!("  {:<8} {:>10.2?} {:>10.1} MiB/s  marker ends at {:?}", format!("{:?}", search), elapsed, throughput, found);
+    }
+}
+
 pub fn advent_day_6()
 {
     let input_data = read_file_to_str("./advent_day_6_1_real");
-    let end_of_marker = scan_datastream(input_data.trim());
+    let (packet, message) = find_markers(input_data.trim().as_bytes());
 
-    println!("End of marker: {}", end_of_marker);
+    println!("End of start-of-packet marker: {:?}", packet);
+    println!("End of start-of-message marker: {:?}", message);
 }
 
 pub fn advent_day_5()
@@ -133,7 +454,133 @@ pub fn advent_day_5()
     let input_data = read_file_to_str("./advent_day_5_1_real");
     let lines = to_untrimmed_lines(&input_data);
 
-    println!("last state of crates: {}", day5::advent::solver(lines));
+    let (old_crane, new_crane) = day5::advent::solver(lines);
+    println!("last state of crates with the CrateMover 9000: {}", old_crane);
+    println!("last state of crates with the CrateMover 9001: {}", new_crane);
+}
+
+pub fn advent_day_5_with_crane(path: &str, model: &str)
+{
+    let input_data = read_file_to_str(path);
+    let lines = to_untrimmed_lines(&input_data);
+
+    if model == "both"
+    {
+        let (old_crane, new_crane) = day5::advent::solver(lines);
+        println!("last state of crates with the CrateMover 9000: {}", old_crane);
+        println!("last state of crates with the CrateMover 9001: {}", new_crane);
+    }
+    else
+    {
+        let crane = match crane_by_model(model)
+        {
+            Some(crane) => crane,
+            None => panic!("There is no CrateMover {}.", model),
+        };
+        println!("last state of crates with the {}: {}", crane.name(), solve_with_crane(lines, crane.as_ref()));
+    }
+}
+
+pub fn advent_day_5_validate(path: &str)
+{
+    let input_data = read_file_to_str(path);
+    let lines = to_untrimmed_lines(&input_data);
+
+    let diagnostics = validate_program(lines);
+    for diagnostic in &diagnostics
+    {
+        println!("{}", describe(diagnostic));
+    }
+    println!("{} invalid move(s) found.", diagnostics.len());
+}
+
+pub fn advent_day_5_lenient(path: &str, model: &str, leniency: Leniency)
+{
+    let input_data = read_file_to_str(path);
+    let lines = to_untrimmed_lines(&input_data);
+
+    let crane = match crane_by_model(model)
+    {
+        Some(crane) => crane,
+        None => panic!("There is no CrateMover {}.", model),
+    };
+
+    let (tops, diagnostics) = execute_lenient(lines, crane.as_ref(), leniency);
+    for diagnostic in &diagnostics
+    {
+        println!("{}", describe(diagnostic));
+    }
+    println!("last state of crates with the {}: {}", crane.name(), tops);
+}
+
+// The target is either a file holding the wanted drawing, or tops= followed by the wanted top crate of every stack.
+pub fn advent_day_5_plan(path: &str, model: &str, target: &str)
+{
+    let input_data = read_file_to_str(path);
+    let (setup, _) = day5::advent::input_processor(to_untrimmed_lines(&input_data));
+    let start = day5::advent::build_stacks(setup).into_inner();
+
+    let crane = match crane_by_model(model)
+    {
+        Some(crane) => crane,
+        None => panic!("There is no CrateMover {}.", model),
+    };
+
+    let target_data = match target.strip_prefix("tops=")
+    {
+        Some(_) => String::new(),
+        None => read_file_to_str(target),
+    };
+    let target = match target.strip_prefix("tops=")
+    {
+        Some(tops) => Target::Tops(tops.split("").filter(|top| !top.is_empty()).collect()),
+        None => 
+        {
+            let drawing: Vec<&str> = to_untrimmed_lines(&target_data).into_iter().filter(|line| !line.is_empty()).collect();
+            Target::Drawing(day5::advent::build_stacks(drawing).into_inner())
+        }
+    };
+
+    match plan(&start, &target, crane.as_ref(), 5_000_000)
+    {
+        Ok(operations) => 
+        {
+            for line in program(&start, &operations)
+            {
+                println!("{}", line);
+            }
+        },
+        Err(PlanError::Unreachable) => println!("The {} cannot reach that target.", crane.name()),
+        Err(PlanError::LimitReached { limit }) => println!("The {} did not reach that target within {} states; it may still be reachable.", crane.name(), limit),
+    }
+}
+
+pub fn advent_day_5_replay(path: &str, model: &str, export: Option<&str>)
+{
+    let input_data = read_file_to_str(path);
+    let lines = to_untrimmed_lines(&input_data);
+
+    let crane = match crane_by_model(model)
+    {
+        Some(crane) => crane,
+        None => panic!("There is no CrateMover {}.", model),
+    };
+
+    let replay = match Replay::record(lines, crane.as_ref())
+    {
+        Ok(replay) => replay,
+        Err(diagnostic) => panic!("The program cannot be replayed: {}", describe(&diagnostic)),
+    };
+    let result = match export
+    {
+        Some(export_path) => replay.export(export_path),
+        None => replay.interactive(std::io::stdin().lock(), &mut std::io::stdout()),
+    };
+
+    if let Err(err) = result
+    {
+        panic!("The replay fell over: {}", err);
+    }
 }
 
 pub fn advent_day_4()
@@ -146,6 +593,22 @@ pub fn advent_day_4()
     println!("The total number of wholly contained pairs is {}, and the number of overlaps is {}", contained_count, overlap_count);
 }
 
+pub fn advent_day_4_population()
+{
+    let input_data = read_file_to_str("./advent_day_4_1_real");
+    let lines = to_lines(&input_data);
+
+    print_population(&lines);
+}
+
+pub fn advent_day_4_coverage()
+{
+    let input_data = read_file_to_str("./advent_day_4_1_real");
+    let lines = to_lines(&input_data);
+
+    print_coverage(&collect_assignments(&lines));
+}
+
 pub fn advent_day_3()
 {
     
@@ -159,6 +622,23 @@ pub fn advent_day_3()
     println!("The badge priority is: {}", badge_priority);
 }
 
+pub fn advent_day_3_repack()
+{
+    let input_data = read_file_to_str("./advent_day_3_1_real");
+    let lines = to_lines(&input_data);
+
+    print_repacking(&plan_repacking(&lines));
+}
+
+pub fn advent_day_3_report(path: &str, format: OutputFormat)
+{
+    let input_data = read_file_to_str(path);
+    let lines = to_lines(&input_data);
+
+    let report = build_report(&lines, 3, 10);
+    println!("{}", render_report(&report, format));
+}
+
 fn read_file_to_str(path: &str) -> String
 {
     if let Ok(input_data) = std::fs::read_to_string(path)
@@ -187,6 +667,31 @@ pub fn advent_day_2()
     }
 }
 
+pub fn advent_day_2_tournament()
+{
+    let input_data = read_file_to_str("./advent_day_2_1_real");
+    let lines = to_lines(&input_data);
+
+    let mut bots: Vec<Box<dyn Strategy>> = vec![
+        Box::new(GuideReplay::mine(&lines)),
+        Box::new(GuideReplay::theirs(&lines)),
+        Box::new(FrequencyCounter::new()),
+        Box::new(Copycat::new()),
+        Box::new(RandomSeeded::new(2022)),
+    ];
+
+    let result = round_robin(&mut bots, lines.len());
+    print_tournament(&result);
+}
+
+pub fn advent_day_2_mixed_strategy()
+{
+    let input_data = read_file_to_str("./advent_day_2_1_real");
+    let lines = to_lines(&input_data);
+
+    print_solution(&solve_guide(&lines));
+}
+
 pub fn advent_day_1()
 {
     // part 1